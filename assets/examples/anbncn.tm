@@ -0,0 +1,10 @@
+// tapes: input, a count, b count
+accepting: accept;
+
+count_a {a, _, _ -> R, x, R, _, N} count_a;
+count_a {b, _, _ -> N, _, L, _, N} count_b;
+count_a {_, _, _ -> N, _, N, _, N} accept;
+count_b {b, x, _ -> R, x, N, x, R} count_b;
+count_b {c, x, _ -> N, x, N, _, L} match_c;
+match_c {c, x, x -> R, x, L, x, L} match_c;
+match_c {_, _, _ -> N, _, N, _, N} accept;
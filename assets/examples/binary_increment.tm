@@ -0,0 +1,8 @@
+accepting: done;
+
+copy {0, _ -> R, 0, R} copy;
+copy {1, _ -> R, 1, R} copy;
+copy {_, _ -> N, _, L} carry;
+carry {_, 1 -> N, 0, L} carry;
+carry {_, 0 -> N, 1, N} done;
+carry {_, _ -> N, 1, N} done;
@@ -0,0 +1,6 @@
+accepting: H;
+
+A {_, _ -> N, 1, R} B;
+A {_, 1 -> N, 1, L} B;
+B {_, _ -> N, 1, L} A;
+B {_, 1 -> N, 1, R} H;
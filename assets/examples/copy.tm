@@ -0,0 +1,9 @@
+// tapes: input, copy, reversed
+accepting: done;
+
+copy {0, _, _ -> R, 0, R, _, N} copy;
+copy {1, _, _ -> R, 1, R, _, N} copy;
+copy {_, _, _ -> N, _, L, _, N} reverse;
+reverse {_, 0, _ -> N, 0, L, 0, R} reverse;
+reverse {_, 1, _ -> N, 1, L, 1, R} reverse;
+reverse {_, _, _ -> N, _, R, _, N} done;
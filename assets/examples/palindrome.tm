@@ -0,0 +1,15 @@
+accepting: accept;
+
+copy {a, _ -> R, a, R} copy;
+copy {b, _ -> R, b, R} copy;
+copy {_, _ -> L, _, L} rewind;
+rewind {a, a -> L, a, N} rewind;
+rewind {a, b -> L, b, N} rewind;
+rewind {b, a -> L, a, N} rewind;
+rewind {b, b -> L, b, N} rewind;
+rewind {_, a -> R, a, N} compare;
+rewind {_, b -> R, b, N} compare;
+rewind {_, _ -> N, _, N} accept;
+compare {a, a -> R, a, L} compare;
+compare {b, b -> R, b, L} compare;
+compare {_, _ -> N, _, N} accept;
@@ -0,0 +1,6 @@
+accepting: done;
+
+first {1, _ -> R, 1, R} first;
+first {+, _ -> R, _, N} second;
+second {1, _ -> R, 1, R} second;
+second {_, _ -> N, _, N} done;
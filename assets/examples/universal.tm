@@ -0,0 +1,266 @@
+// tapes: description, simulated tape, state
+accepting: accept;
+
+// find the 111 before the input word
+find0 {0, _, _ -> R, _, N, _, N} find0;
+find0 {1, _, _ -> R, _, N, _, N} find1;
+find1 {0, _, _ -> R, _, N, _, N} find0;
+find1 {1, _, _ -> R, _, N, _, N} find2;
+find2 {0, _, _ -> R, _, N, _, N} find0;
+find2 {1, _, _ -> R, _, N, _, N} copy;
+
+// copy the input word on the simulated tape, then go back to its start in state 1
+copy {0, _, _ -> R, 0, R, _, N} copy;
+copy {1, _, _ -> R, 1, R, _, N} copy;
+copy {_, _, _ -> N, _, L, _, N} back_tape;
+back_tape {0, 0, _ -> N, 0, L, _, N} back_tape;
+back_tape {1, 0, _ -> N, 0, L, _, N} back_tape;
+back_tape {_, 0, _ -> N, 0, L, _, N} back_tape;
+back_tape {0, 1, _ -> N, 1, L, _, N} back_tape;
+back_tape {1, 1, _ -> N, 1, L, _, N} back_tape;
+back_tape {_, 1, _ -> N, 1, L, _, N} back_tape;
+back_tape {0, _, _ -> L, _, R, x, N} back_input;
+back_tape {1, _, _ -> L, _, R, x, N} back_input;
+back_tape {_, _, _ -> L, _, R, x, N} back_input;
+back_input {0, 0, x -> L, 0, N, x, N} back_input;
+back_input {0, 1, x -> L, 1, N, x, N} back_input;
+back_input {0, _, x -> L, _, N, x, N} back_input;
+back_input {1, 0, x -> L, 0, N, x, N} back_input;
+back_input {1, 1, x -> L, 1, N, x, N} back_input;
+back_input {1, _, x -> L, _, N, x, N} back_input;
+back_input {_, 0, x -> R, 0, N, x, N} code_start;
+back_input {_, 1, x -> R, 1, N, x, N} code_start;
+back_input {_, _, x -> R, _, N, x, N} code_start;
+
+// compare the state of a rule with the current one, or halt after the last rule
+code_start {0, 0, x -> R, 0, N, x, R} match_state;
+code_start {0, 1, x -> R, 1, N, x, R} match_state;
+code_start {0, _, x -> R, _, N, x, R} match_state;
+code_start {1, 0, x -> N, 0, N, x, R} halt_check;
+code_start {1, 1, x -> N, 1, N, x, R} halt_check;
+code_start {1, _, x -> N, _, N, x, R} halt_check;
+match_state {0, 0, x -> R, 0, N, x, R} match_state;
+match_state {0, 1, x -> R, 1, N, x, R} match_state;
+match_state {0, _, x -> R, _, N, x, R} match_state;
+match_state {1, 0, _ -> R, 0, N, _, N} symbol0;
+match_state {1, 1, _ -> R, 1, N, _, N} symbol0;
+match_state {1, _, _ -> R, _, N, _, N} symbol0;
+match_state {0, 0, _ -> N, 0, N, _, N} skip0;
+match_state {0, 1, _ -> N, 1, N, _, N} skip0;
+match_state {0, _, _ -> N, _, N, _, N} skip0;
+match_state {1, 0, x -> N, 0, N, x, N} skip0;
+match_state {1, 1, x -> N, 1, N, x, N} skip0;
+match_state {1, _, x -> N, _, N, x, N} skip0;
+
+// compare the symbol of a rule with the one under the simulated head
+symbol0 {0, 0, _ -> R, 0, N, _, N} symbol1;
+symbol0 {0, 1, _ -> R, 1, N, _, N} symbol1;
+symbol0 {0, _, _ -> R, _, N, _, N} symbol1;
+symbol1 {0, 0, _ -> R, 0, N, _, N} symbol2;
+symbol1 {0, 1, _ -> R, 1, N, _, N} symbol2;
+symbol1 {0, _, _ -> R, _, N, _, N} symbol2;
+symbol1 {1, 0, _ -> R, 0, N, _, N} erase;
+symbol1 {1, 1, _ -> N, 1, N, _, N} skip0;
+symbol1 {1, _, _ -> N, _, N, _, N} skip0;
+symbol2 {0, 0, _ -> R, 0, N, _, N} symbol3;
+symbol2 {0, 1, _ -> R, 1, N, _, N} symbol3;
+symbol2 {0, _, _ -> R, _, N, _, N} symbol3;
+symbol2 {1, 0, _ -> N, 0, N, _, N} skip0;
+symbol2 {1, 1, _ -> R, 1, N, _, N} erase;
+symbol2 {1, _, _ -> N, _, N, _, N} skip0;
+symbol3 {0, 0, _ -> N, 0, N, _, N} skip0;
+symbol3 {0, 1, _ -> N, 1, N, _, N} skip0;
+symbol3 {0, _, _ -> N, _, N, _, N} skip0;
+symbol3 {1, 0, _ -> N, 0, N, _, N} skip0;
+symbol3 {1, 1, _ -> N, 1, N, _, N} skip0;
+symbol3 {1, _, _ -> R, _, N, _, N} erase;
+
+// go to the next rule, and back to the start of the state
+skip0 {0, 0, x -> R, 0, N, x, N} skip0;
+skip0 {0, 0, _ -> R, 0, N, _, N} skip0;
+skip0 {0, 1, x -> R, 1, N, x, N} skip0;
+skip0 {0, 1, _ -> R, 1, N, _, N} skip0;
+skip0 {0, _, x -> R, _, N, x, N} skip0;
+skip0 {0, _, _ -> R, _, N, _, N} skip0;
+skip1 {0, 0, x -> R, 0, N, x, N} skip0;
+skip1 {0, 0, _ -> R, 0, N, _, N} skip0;
+skip1 {0, 1, x -> R, 1, N, x, N} skip0;
+skip1 {0, 1, _ -> R, 1, N, _, N} skip0;
+skip1 {0, _, x -> R, _, N, x, N} skip0;
+skip1 {0, _, _ -> R, _, N, _, N} skip0;
+skip0 {1, 0, x -> R, 0, N, x, N} skip1;
+skip0 {1, 0, _ -> R, 0, N, _, N} skip1;
+skip0 {1, 1, x -> R, 1, N, x, N} skip1;
+skip0 {1, 1, _ -> R, 1, N, _, N} skip1;
+skip0 {1, _, x -> R, _, N, x, N} skip1;
+skip0 {1, _, _ -> R, _, N, _, N} skip1;
+skip1 {1, 0, x -> R, 0, N, x, L} rewind_state;
+skip1 {1, 0, _ -> R, 0, N, _, L} rewind_state;
+skip1 {1, 1, x -> R, 1, N, x, L} rewind_state;
+skip1 {1, 1, _ -> R, 1, N, _, L} rewind_state;
+skip1 {1, _, x -> R, _, N, x, L} rewind_state;
+skip1 {1, _, _ -> R, _, N, _, L} rewind_state;
+rewind_state {0, 0, x -> N, 0, N, x, L} rewind_state;
+rewind_state {0, 1, x -> N, 1, N, x, L} rewind_state;
+rewind_state {0, _, x -> N, _, N, x, L} rewind_state;
+rewind_state {1, 0, x -> N, 0, N, x, L} rewind_state;
+rewind_state {1, 1, x -> N, 1, N, x, L} rewind_state;
+rewind_state {1, _, x -> N, _, N, x, L} rewind_state;
+rewind_state {_, 0, x -> N, 0, N, x, L} rewind_state;
+rewind_state {_, 1, x -> N, 1, N, x, L} rewind_state;
+rewind_state {_, _, x -> N, _, N, x, L} rewind_state;
+rewind_state {0, 0, _ -> N, 0, N, _, R} code_start;
+rewind_state {0, 1, _ -> N, 1, N, _, R} code_start;
+rewind_state {0, _, _ -> N, _, N, _, R} code_start;
+rewind_state {1, 0, _ -> N, 0, N, _, R} code_start;
+rewind_state {1, 1, _ -> N, 1, N, _, R} code_start;
+rewind_state {1, _, _ -> N, _, N, _, R} code_start;
+rewind_state {_, 0, _ -> N, 0, N, _, R} code_start;
+rewind_state {_, 1, _ -> N, 1, N, _, R} code_start;
+rewind_state {_, _, _ -> N, _, N, _, R} code_start;
+
+// replace the state by the one of the matching rule
+erase {0, 0, _ -> N, 0, N, _, L} erase_loop;
+erase {0, 1, _ -> N, 1, N, _, L} erase_loop;
+erase {0, _, _ -> N, _, N, _, L} erase_loop;
+erase {1, 0, _ -> N, 0, N, _, L} erase_loop;
+erase {1, 1, _ -> N, 1, N, _, L} erase_loop;
+erase {1, _, _ -> N, _, N, _, L} erase_loop;
+erase {_, 0, _ -> N, 0, N, _, L} erase_loop;
+erase {_, 1, _ -> N, 1, N, _, L} erase_loop;
+erase {_, _, _ -> N, _, N, _, L} erase_loop;
+erase_loop {0, 0, x -> N, 0, N, _, L} erase_loop;
+erase_loop {0, 1, x -> N, 1, N, _, L} erase_loop;
+erase_loop {0, _, x -> N, _, N, _, L} erase_loop;
+erase_loop {1, 0, x -> N, 0, N, _, L} erase_loop;
+erase_loop {1, 1, x -> N, 1, N, _, L} erase_loop;
+erase_loop {1, _, x -> N, _, N, _, L} erase_loop;
+erase_loop {_, 0, x -> N, 0, N, _, L} erase_loop;
+erase_loop {_, 1, x -> N, 1, N, _, L} erase_loop;
+erase_loop {_, _, x -> N, _, N, _, L} erase_loop;
+erase_loop {0, 0, _ -> N, 0, N, _, R} set_state;
+erase_loop {0, 1, _ -> N, 1, N, _, R} set_state;
+erase_loop {0, _, _ -> N, _, N, _, R} set_state;
+erase_loop {1, 0, _ -> N, 0, N, _, R} set_state;
+erase_loop {1, 1, _ -> N, 1, N, _, R} set_state;
+erase_loop {1, _, _ -> N, _, N, _, R} set_state;
+erase_loop {_, 0, _ -> N, 0, N, _, R} set_state;
+erase_loop {_, 1, _ -> N, 1, N, _, R} set_state;
+erase_loop {_, _, _ -> N, _, N, _, R} set_state;
+set_state {0, 0, _ -> R, 0, N, x, R} set_state;
+set_state {0, 1, _ -> R, 1, N, x, R} set_state;
+set_state {0, _, _ -> R, _, N, x, R} set_state;
+set_state {1, 0, _ -> R, 0, N, _, N} write0;
+set_state {1, 1, _ -> R, 1, N, _, N} write0;
+set_state {1, _, _ -> R, _, N, _, N} write0;
+
+// write the symbol of the rule and move the simulated head
+write0 {0, 0, _ -> R, 0, N, _, N} write1;
+write0 {0, 1, _ -> R, 1, N, _, N} write1;
+write0 {0, _, _ -> R, _, N, _, N} write1;
+write1 {0, 0, _ -> R, 0, N, _, N} write2;
+write1 {0, 1, _ -> R, 1, N, _, N} write2;
+write1 {0, _, _ -> R, _, N, _, N} write2;
+write1 {1, 0, _ -> R, 0, N, _, N} move1_0;
+write1 {1, 1, _ -> R, 1, N, _, N} move1_0;
+write1 {1, _, _ -> R, _, N, _, N} move1_0;
+move1_0 {0, 0, _ -> R, 0, N, _, N} move1_1;
+move1_0 {0, 1, _ -> R, 1, N, _, N} move1_1;
+move1_0 {0, _, _ -> R, _, N, _, N} move1_1;
+move1_1 {0, 0, _ -> R, 0, N, _, N} move1_2;
+move1_1 {0, 1, _ -> R, 1, N, _, N} move1_2;
+move1_1 {0, _, _ -> R, _, N, _, N} move1_2;
+move1_1 {1, 0, _ -> L, 0, L, _, N} restart_input;
+move1_1 {1, 1, _ -> L, 0, L, _, N} restart_input;
+move1_1 {1, _, _ -> L, 0, L, _, N} restart_input;
+move1_2 {0, 0, _ -> R, 0, N, _, N} move1_3;
+move1_2 {0, 1, _ -> R, 1, N, _, N} move1_3;
+move1_2 {0, _, _ -> R, _, N, _, N} move1_3;
+move1_2 {1, 0, _ -> L, 0, R, _, N} restart_input;
+move1_2 {1, 1, _ -> L, 0, R, _, N} restart_input;
+move1_2 {1, _, _ -> L, 0, R, _, N} restart_input;
+move1_3 {1, 0, _ -> L, 0, N, _, N} restart_input;
+move1_3 {1, 1, _ -> L, 0, N, _, N} restart_input;
+move1_3 {1, _, _ -> L, 0, N, _, N} restart_input;
+write2 {0, 0, _ -> R, 0, N, _, N} write3;
+write2 {0, 1, _ -> R, 1, N, _, N} write3;
+write2 {0, _, _ -> R, _, N, _, N} write3;
+write2 {1, 0, _ -> R, 0, N, _, N} move2_0;
+write2 {1, 1, _ -> R, 1, N, _, N} move2_0;
+write2 {1, _, _ -> R, _, N, _, N} move2_0;
+move2_0 {0, 0, _ -> R, 0, N, _, N} move2_1;
+move2_0 {0, 1, _ -> R, 1, N, _, N} move2_1;
+move2_0 {0, _, _ -> R, _, N, _, N} move2_1;
+move2_1 {0, 0, _ -> R, 0, N, _, N} move2_2;
+move2_1 {0, 1, _ -> R, 1, N, _, N} move2_2;
+move2_1 {0, _, _ -> R, _, N, _, N} move2_2;
+move2_1 {1, 0, _ -> L, 1, L, _, N} restart_input;
+move2_1 {1, 1, _ -> L, 1, L, _, N} restart_input;
+move2_1 {1, _, _ -> L, 1, L, _, N} restart_input;
+move2_2 {0, 0, _ -> R, 0, N, _, N} move2_3;
+move2_2 {0, 1, _ -> R, 1, N, _, N} move2_3;
+move2_2 {0, _, _ -> R, _, N, _, N} move2_3;
+move2_2 {1, 0, _ -> L, 1, R, _, N} restart_input;
+move2_2 {1, 1, _ -> L, 1, R, _, N} restart_input;
+move2_2 {1, _, _ -> L, 1, R, _, N} restart_input;
+move2_3 {1, 0, _ -> L, 1, N, _, N} restart_input;
+move2_3 {1, 1, _ -> L, 1, N, _, N} restart_input;
+move2_3 {1, _, _ -> L, 1, N, _, N} restart_input;
+write3 {1, 0, _ -> R, 0, N, _, N} move3_0;
+write3 {1, 1, _ -> R, 1, N, _, N} move3_0;
+write3 {1, _, _ -> R, _, N, _, N} move3_0;
+move3_0 {0, 0, _ -> R, 0, N, _, N} move3_1;
+move3_0 {0, 1, _ -> R, 1, N, _, N} move3_1;
+move3_0 {0, _, _ -> R, _, N, _, N} move3_1;
+move3_1 {0, 0, _ -> R, 0, N, _, N} move3_2;
+move3_1 {0, 1, _ -> R, 1, N, _, N} move3_2;
+move3_1 {0, _, _ -> R, _, N, _, N} move3_2;
+move3_1 {1, 0, _ -> L, _, L, _, N} restart_input;
+move3_1 {1, 1, _ -> L, _, L, _, N} restart_input;
+move3_1 {1, _, _ -> L, _, L, _, N} restart_input;
+move3_2 {0, 0, _ -> R, 0, N, _, N} move3_3;
+move3_2 {0, 1, _ -> R, 1, N, _, N} move3_3;
+move3_2 {0, _, _ -> R, _, N, _, N} move3_3;
+move3_2 {1, 0, _ -> L, _, R, _, N} restart_input;
+move3_2 {1, 1, _ -> L, _, R, _, N} restart_input;
+move3_2 {1, _, _ -> L, _, R, _, N} restart_input;
+move3_3 {1, 0, _ -> L, _, N, _, N} restart_input;
+move3_3 {1, 1, _ -> L, _, N, _, N} restart_input;
+move3_3 {1, _, _ -> L, _, N, _, N} restart_input;
+
+// go back to the first rule
+restart_input {0, 0, _ -> L, 0, N, _, N} restart_input;
+restart_input {0, 1, _ -> L, 1, N, _, N} restart_input;
+restart_input {0, _, _ -> L, _, N, _, N} restart_input;
+restart_input {1, 0, _ -> L, 0, N, _, N} restart_input;
+restart_input {1, 1, _ -> L, 1, N, _, N} restart_input;
+restart_input {1, _, _ -> L, _, N, _, N} restart_input;
+restart_input {_, 0, _ -> R, 0, N, _, L} restart_state;
+restart_input {_, 1, _ -> R, 1, N, _, L} restart_state;
+restart_input {_, _, _ -> R, _, N, _, L} restart_state;
+restart_state {0, 0, x -> N, 0, N, x, L} restart_state;
+restart_state {0, 1, x -> N, 1, N, x, L} restart_state;
+restart_state {0, _, x -> N, _, N, x, L} restart_state;
+restart_state {1, 0, x -> N, 0, N, x, L} restart_state;
+restart_state {1, 1, x -> N, 1, N, x, L} restart_state;
+restart_state {1, _, x -> N, _, N, x, L} restart_state;
+restart_state {_, 0, x -> N, 0, N, x, L} restart_state;
+restart_state {_, 1, x -> N, 1, N, x, L} restart_state;
+restart_state {_, _, x -> N, _, N, x, L} restart_state;
+restart_state {0, 0, _ -> N, 0, N, _, R} code_start;
+restart_state {0, 1, _ -> N, 1, N, _, R} code_start;
+restart_state {0, _, _ -> N, _, N, _, R} code_start;
+restart_state {1, 0, _ -> N, 0, N, _, R} code_start;
+restart_state {1, 1, _ -> N, 1, N, _, R} code_start;
+restart_state {1, _, _ -> N, _, N, _, R} code_start;
+restart_state {_, 0, _ -> N, 0, N, _, R} code_start;
+restart_state {_, 1, _ -> N, 1, N, _, R} code_start;
+restart_state {_, _, _ -> N, _, N, _, R} code_start;
+
+// accept if the machine halts in state 2
+halt_check {1, 0, x -> N, 0, N, x, R} halt_two;
+halt_check {1, 1, x -> N, 1, N, x, R} halt_two;
+halt_check {1, _, x -> N, _, N, x, R} halt_two;
+halt_two {1, 0, _ -> N, 0, N, _, N} accept;
+halt_two {1, 1, _ -> N, 1, N, _, N} accept;
+halt_two {1, _, _ -> N, _, N, _, N} accept;
@@ -0,0 +1,291 @@
+use std::collections::{BTreeSet, HashSet, VecDeque};
+
+use itertools::Itertools;
+use turingrs::turing_machine::TuringMachine;
+
+use crate::source::{self, BLANK};
+
+/// Above this number of symbol combinations of a state, missing cases are not listed
+const MAX_COMBINATIONS: usize = 512;
+/// Number of missing cases written in a problem
+const SHOWN_COMBINATIONS: usize = 5;
+
+/// Kind of problem found in a machine
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Lint {
+    Unreachable,
+    DeadEnd,
+    FinalWithRules,
+    NeverWritten,
+    NeverRead,
+    Missing,
+    Overlapping,
+}
+
+impl Lint {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::Unreachable => "unreachable state",
+            Lint::DeadEnd => "dead end",
+            Lint::FinalWithRules => "final state with rules",
+            Lint::NeverWritten => "symbol never written",
+            Lint::NeverRead => "symbol never read",
+            Lint::Missing => "missing cases",
+            Lint::Overlapping => "nondeterminism",
+        }
+    }
+}
+
+/// A problem found in a machine, with the state or the rule it is about
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub lint: Lint,
+    pub message: String,
+    pub state: Option<u8>,
+    /// State and index of the transition in the state
+    pub rule: Option<(u8, usize)>,
+}
+
+/// Look for the usual mistakes in a machine, the initial state being the first one
+pub fn analyze(tm: &TuringMachine) -> Vec<Problem> {
+    let mut problems = vec![];
+    let reachable = reachable(tm);
+    let name = |i: u8| source::state_name(tm, i);
+
+    for index in 0..tm.states.len() as u8 {
+        let state = tm.get_state(index);
+
+        if !reachable.contains(&index) {
+            problems.push(Problem {
+                lint: Lint::Unreachable,
+                message: format!("State '{}' can not be reached from the initial state", name(index)),
+                state: Some(index),
+                rule: None,
+            });
+        }
+
+        if state.transitions.is_empty() && !state.is_final {
+            problems.push(Problem {
+                lint: Lint::DeadEnd,
+                message: format!("State '{}' has no rule and is not accepting, it always refuses", name(index)),
+                state: Some(index),
+                rule: None,
+            });
+        }
+
+        if !state.transitions.is_empty() && state.is_final {
+            problems.push(Problem {
+                lint: Lint::FinalWithRules,
+                message: format!("Accepting state '{}' has rules", name(index)),
+                state: Some(index),
+                rule: None,
+            });
+        }
+
+        // rules of the state reading the same symbols
+        for ((i, a), (j, b)) in state.transitions.iter().enumerate().tuple_combinations() {
+            if a.chars_read == b.chars_read {
+                problems.push(Problem {
+                    lint: Lint::Overlapping,
+                    message: format!(
+                        "State '{}' has rules {} and {} reading ({}), the machine is nondeterministic",
+                        name(index),
+                        i + 1,
+                        j + 1,
+                        a.chars_read.iter().join(", ")
+                    ),
+                    state: Some(index),
+                    rule: Some((index, j)),
+                });
+            }
+        }
+    }
+
+    problems.extend(symbols(tm));
+    problems.extend(missing(tm, &reachable));
+    problems
+}
+
+/// States reachable from the initial state
+pub fn reachable(tm: &TuringMachine) -> HashSet<u8> {
+    let mut seen = HashSet::new();
+    if tm.states.is_empty() {
+        return seen;
+    }
+
+    let mut queue = VecDeque::from([0u8]);
+    seen.insert(0);
+    while let Some(index) = queue.pop_front() {
+        for t in tm.get_state(index).transitions.iter() {
+            if seen.insert(t.index_to_state) {
+                queue.push_back(t.index_to_state);
+            }
+        }
+    }
+    seen
+}
+
+// symbols read on a write ribbon but never written on it, and the other way around
+fn symbols(tm: &TuringMachine) -> Vec<Problem> {
+    let mut problems = vec![];
+
+    for ribbon in 1..=tm.k as usize {
+        let (read, written) = alphabets(tm, ribbon);
+
+        for c in read.difference(&written).filter(|c| **c != BLANK) {
+            problems.push(Problem {
+                lint: Lint::NeverWritten,
+                message: format!("Symbol '{}' is read on write ribbon {} but never written on it", c, ribbon),
+                state: None,
+                rule: None,
+            });
+        }
+        for c in written.difference(&read).filter(|c| **c != BLANK) {
+            problems.push(Problem {
+                lint: Lint::NeverRead,
+                message: format!("Symbol '{}' is written on write ribbon {} but never read on it", c, ribbon),
+                state: None,
+                rule: None,
+            });
+        }
+    }
+    problems
+}
+
+// symbols read and written on a ribbon, 0 being the read ribbon that is never written
+fn alphabets(tm: &TuringMachine, ribbon: usize) -> (BTreeSet<char>, BTreeSet<char>) {
+    let mut read = BTreeSet::new();
+    let mut written = BTreeSet::new();
+    for state in tm.states.iter() {
+        for t in state.transitions.iter() {
+            read.extend(t.chars_read.get(ribbon));
+            if ribbon > 0 {
+                written.extend(t.chars_write.get(ribbon - 1).map(|(c, _)| *c));
+            }
+        }
+    }
+    (read, written)
+}
+
+/// Symbols a ribbon can hold: the blank first, then the symbols the machine reads or writes on it
+pub fn ribbon_symbols(tm: &TuringMachine, ribbon: usize) -> Vec<char> {
+    let (mut symbols, written) = alphabets(tm, ribbon);
+    symbols.extend(written);
+    symbols.remove(&BLANK);
+    std::iter::once(BLANK).chain(symbols).collect()
+}
+
+// combinations of symbols that a reachable state can read but has no rule for
+fn missing(tm: &TuringMachine, reachable: &HashSet<u8>) -> Vec<Problem> {
+    let alphabets: Vec<Vec<char>> = (0..=tm.k as usize).map(|ribbon| ribbon_symbols(tm, ribbon)).collect();
+
+    let count: usize = alphabets.iter().map(Vec::len).product();
+    if count > MAX_COMBINATIONS {
+        return vec![Problem {
+            lint: Lint::Missing,
+            message: format!(
+                "The ribbons can hold {} combinations of symbols, more than the {} checked for missing cases",
+                count, MAX_COMBINATIONS
+            ),
+            state: None,
+            rule: None,
+        }];
+    }
+
+    let mut problems = vec![];
+    for index in (0..tm.states.len() as u8).filter(|i| reachable.contains(i)) {
+        let state = tm.get_state(index);
+        if state.is_final || state.transitions.is_empty() {
+            continue;
+        }
+
+        let missing: Vec<Vec<char>> = alphabets
+            .iter()
+            .multi_cartesian_product()
+            .map(|combination| combination.into_iter().copied().collect::<Vec<char>>())
+            .filter(|combination| !state.transitions.iter().any(|t| t.chars_read == *combination))
+            .collect();
+
+        if missing.is_empty() {
+            continue;
+        }
+
+        let mut shown = missing
+            .iter()
+            .take(SHOWN_COMBINATIONS)
+            .map(|c| format!("({})", c.iter().join(", ")))
+            .join(", ");
+        if missing.len() > SHOWN_COMBINATIONS {
+            shown += &format!(" and {} more", missing.len() - SHOWN_COMBINATIONS);
+        }
+
+        problems.push(Problem {
+            lint: Lint::Missing,
+            message: format!("State '{}' has no rule for {}", source::state_name(tm, index), shown),
+            state: Some(index),
+            rule: None,
+        });
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // start loops on a and goes to done on b, loop is never reached
+    const CODE: &str = "accepting: done;\n\n\
+        start {a, _ -> R, x, R} start;\n\
+        start {b, _ -> R, _, N} done;\n\
+        done {a, _ -> R, _, N} done;\n\
+        loop {a, x -> R, _, N} loop;\n\
+        loop {a, x -> R, y, N} stuck;";
+
+    fn lints(problems: &[Problem], lint: Lint) -> Vec<&Problem> {
+        problems.iter().filter(|p| p.lint == lint).collect()
+    }
+
+    #[test]
+    fn reachable_states() {
+        let tm = source::parse(CODE).unwrap();
+        let reached: BTreeSet<String> = reachable(&tm).into_iter().map(|i| source::state_name(&tm, i)).collect();
+        assert_eq!(reached, BTreeSet::from([String::from("start"), String::from("done")]));
+    }
+
+    #[test]
+    fn usual_mistakes_are_found() {
+        let tm = source::parse(CODE).unwrap();
+        let problems = analyze(&tm);
+        let state = |lint| {
+            let names = lints(&problems, lint).iter().map(|p| source::state_name(&tm, p.state.unwrap()));
+            names.sorted().collect::<Vec<_>>()
+        };
+
+        assert_eq!(state(Lint::Unreachable), ["loop", "stuck"]);
+        assert_eq!(state(Lint::DeadEnd), ["stuck"]);
+        assert_eq!(state(Lint::FinalWithRules), ["done"]);
+        assert_eq!(state(Lint::Overlapping), ["loop"]);
+        // y is written but not read, x is read and written
+        assert_eq!(lints(&problems, Lint::NeverRead).len(), 1);
+        assert!(lints(&problems, Lint::NeverWritten).is_empty());
+    }
+
+    #[test]
+    fn missing_cases_of_reachable_states() {
+        let tm = source::parse(CODE).unwrap();
+        let missing = missing(&tm, &reachable(&tm));
+        // one problem for start listing its 7 missing cases, done accepts and the others are unreachable
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].state.map(|i| source::state_name(&tm, i)).as_deref(), Some("start"));
+    }
+
+    #[test]
+    fn missing_cases_are_skipped_on_large_alphabets() {
+        let symbols: Vec<char> = ('a'..='z').collect();
+        let rules: String = symbols.iter().map(|c| format!("q {{{}, {} -> R, {}, N}} q;\n", c, c, c)).collect();
+        let tm = source::parse(&format!("accepting: q;\n\n{}", rules)).unwrap();
+        let missing = missing(&tm, &reachable(&tm));
+        assert_eq!(missing.len(), 1);
+        assert!(missing[0].state.is_none());
+    }
+}
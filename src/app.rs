@@ -1,5 +1,5 @@
 use std::{
-    collections::{BTreeMap, HashMap}, fs::File, path::PathBuf
+    collections::{BTreeMap, HashMap, HashSet}, fs::File, path::PathBuf
 };
 
 use poll_promise::Promise;
@@ -12,11 +12,23 @@ use egui::{
 use egui_extras::install_image_loaders;
 use rand::random_range;
 use turingrs::{
-    parser::parse_turing_machine,
     turing_machine::{TuringExecutionStep, TuringExecutor, TuringMachineExecutor},
 };
 
-use crate::ui::{self, turing::{State, Transition}};
+use crate::{
+    analysis::{self, Problem},
+    beaver,
+    compose, equivalence, fuzz,
+    ui::constant::Constant,
+    examples::{EXAMPLES, Example},
+    format::{Format, ImportedMachine},
+    ntm,
+    profile::{self, Inputs},
+    source, tape,
+    transform::cleanup::{self, Change, Cleanup},
+    universal,
+    ui::{self, turing::{State, Transition}},
+};
 
 pub struct TuringApp {
     pub turing: TuringMachineExecutor,
@@ -31,7 +43,99 @@ pub struct TuringApp {
     pub count: usize,
     pub is_accepted: Option<bool>,
     pub promise: Option<Promise<Option<PathBuf>>>,
-    pub promise_wasm: Option<Promise<Option<FileHandle>>>
+    pub promise_wasm: Option<Promise<Option<FileHandle>>>,
+    pub import_promise: Option<(Format, Promise<Option<String>>)>,
+    pub messages: Vec<String>,
+    pub organic_layout: bool,
+    pub initial_step: Option<TuringExecutionStep>,
+    pub editing_cell: Option<(usize, i32)>,
+    pub cell_text: String,
+    pub follow_head: bool,
+    pub tape_centers: Vec<f32>,
+    /// Cells added before the first cell of every ribbon since the input was loaded,
+    /// so that the indices of the cells stay the same when a ribbon grows to the left
+    pub origins: Vec<usize>,
+    pub initial_origins: Vec<usize>,
+    pub jump_index: String,
+    pub running: bool,
+    pub speed: f32,
+    pub last_run_time: f64,
+    pub previous_step: Option<TuringExecutionStep>,
+    pub animation_start: Option<f64>,
+    pub tape_names: Vec<String>,
+    pub collapsed_ribbons: HashSet<usize>,
+    pub history: Vec<TuringExecutionStep>,
+    pub show_history: bool,
+    pub history_ribbon: usize,
+    pub history_zoom: f32,
+    pub show_watch: bool,
+    pub previous_state: Option<u8>,
+    pub spans: source::Spans,
+    pub code_cursor: Option<usize>,
+    pub problems: Vec<Problem>,
+    pub branch: Option<Vec<(u8, TuringExecutionStep)>>,
+    pub ntm_tree: Option<ntm::Tree>,
+    pub show_ntm: bool,
+    pub ntm_depth: usize,
+    pub ntm_nodes: usize,
+    pub selected_branch: Option<usize>,
+    pub macros: Vec<compose::Macro>,
+    pub expanded_macros: HashSet<String>,
+    pub show_profile: bool,
+    pub profile_random: bool,
+    pub profile_pattern: String,
+    pub profile_alphabet: String,
+    pub profile_count: usize,
+    pub profile_max: usize,
+    pub profile_budget: usize,
+    pub profiler: Option<profile::Profiler>,
+    pub profile_error: Option<String>,
+    pub show_equivalence: bool,
+    pub second_code: String,
+    pub equivalence_alphabet: String,
+    pub equivalence_length: usize,
+    pub equivalence_budget: usize,
+    pub equivalence_outputs: bool,
+    pub equivalence_report: Option<Result<equivalence::Checker, String>>,
+    pub show_fuzz: bool,
+    pub fuzz_oracle: fuzz::OracleKind,
+    pub fuzz_regex: String,
+    pub fuzz_list: String,
+    pub fuzz_alphabet: String,
+    pub fuzz_runs: usize,
+    pub fuzz_length: usize,
+    pub fuzz_budget: usize,
+    pub fuzz_campaign: Option<Result<fuzz::Fuzzer, String>>,
+    pub test_failures: Option<Result<fuzz::Tester, String>>,
+    pub show_beaver: bool,
+    pub beaver_states: usize,
+    pub beaver_symbols: usize,
+    pub beaver_steps: usize,
+    pub beaver_machines: usize,
+    pub beaver_keep: usize,
+    pub beaver_result: Option<Result<beaver::Enumerator, String>>,
+    pub show_automaton: bool,
+    pub automaton_from_table: bool,
+    pub automaton_regex: String,
+    pub automaton_table: String,
+    pub automaton_error: Option<String>,
+    pub show_universal: bool,
+    pub universal_word: String,
+    pub universal_description: String,
+    pub universal_error: Option<String>,
+    pub show_cleanup: bool,
+    pub cleanup: Cleanup,
+    pub cleanup_preview: Option<(Cleanup, String, Result<(String, Vec<Change>), String>)>,
+    pub undo: Vec<Snapshot>,
+}
+
+/// Code and layout of the machine before an edit, to undo it
+pub struct Snapshot {
+    pub label: &'static str,
+    pub code: String,
+    pub positions: HashMap<String, Pos2>,
+    /// Code produced by the edit, the snapshot being stale once it is changed
+    pub after: String,
 }
 
 impl Default for TuringApp {
@@ -65,6 +169,87 @@ impl Default for TuringApp {
             is_accepted: None,
             promise: None,
             promise_wasm: None,
+            import_promise: None,
+            messages: vec![],
+            organic_layout: true,
+            initial_step: None,
+            editing_cell: None,
+            cell_text: String::new(),
+            follow_head: true,
+            tape_centers: vec![],
+            origins: vec![],
+            initial_origins: vec![],
+            jump_index: String::new(),
+            running: false,
+            speed: 5.0,
+            last_run_time: 0.0,
+            previous_step: None,
+            animation_start: None,
+            tape_names: vec![],
+            collapsed_ribbons: HashSet::new(),
+            history: vec![],
+            show_history: false,
+            history_ribbon: 0,
+            history_zoom: 10.0,
+            show_watch: false,
+            previous_state: None,
+            spans: source::Spans::default(),
+            code_cursor: None,
+            problems: vec![],
+            branch: None,
+            ntm_tree: None,
+            show_ntm: false,
+            ntm_depth: 100,
+            ntm_nodes: 2000,
+            selected_branch: None,
+            macros: vec![],
+            expanded_macros: HashSet::new(),
+            show_profile: false,
+            profile_random: false,
+            profile_pattern: String::from("a^n b^n"),
+            profile_alphabet: String::new(),
+            profile_count: 5,
+            profile_max: 20,
+            profile_budget: 100_000,
+            profiler: None,
+            profile_error: None,
+            show_equivalence: false,
+            second_code: String::new(),
+            equivalence_alphabet: String::new(),
+            equivalence_length: 6,
+            equivalence_budget: 10_000,
+            equivalence_outputs: false,
+            equivalence_report: None,
+            show_fuzz: false,
+            fuzz_oracle: fuzz::OracleKind::Regex,
+            fuzz_regex: String::new(),
+            fuzz_list: String::new(),
+            fuzz_alphabet: String::new(),
+            fuzz_runs: 200,
+            fuzz_length: 10,
+            fuzz_budget: 10_000,
+            fuzz_campaign: None,
+            test_failures: None,
+            show_beaver: false,
+            beaver_states: 2,
+            beaver_symbols: 2,
+            beaver_steps: 200,
+            beaver_machines: 1_000_000,
+            beaver_keep: 10,
+            beaver_result: None,
+            show_automaton: false,
+            automaton_from_table: false,
+            automaton_regex: String::new(),
+            automaton_table: String::new(),
+            automaton_error: None,
+            show_universal: false,
+            universal_word: String::new(),
+            universal_description: String::new(),
+            universal_error: None,
+            show_cleanup: false,
+            cleanup: Cleanup::Unreachable,
+            cleanup_preview: None,
+            undo: vec![],
         }
     }
 }
@@ -90,8 +275,43 @@ impl TuringApp {
 
     /// Compile the code by creating a new TuringMachine and TuringMachineExecutor and updating the graph
     pub fn compile(&mut self) {
-        let tm = parse_turing_machine(self.code.clone()).unwrap();
+        let composed = match compose::expand(&self.code) {
+            Ok(composed) => composed,
+            Err(e) => {
+                self.messages = vec![e];
+                return;
+            }
+        };
+        let tm = match source::parse(&composed.code) {
+            Ok(tm) => tm,
+            Err(e) => {
+                self.messages = vec![e];
+                return;
+            }
+        };
+        self.macros = composed.macros;
+        self.tape_names = source::tape_names(&self.code);
+        self.spans = source::spans(&self.code, &tm);
+        self.problems = analysis::analyze(&tm);
         (self.turing, self.current_step) = TuringMachineExecutor::new(tm, "".to_string()).unwrap();
+        self.build_graph();
+        self.current_step = TuringExecutionStep::new(self.turing.turing_machine.k);
+        self.count = 0;
+        self.is_accepted = None;
+        self.organic_layout = true;
+        self.initial_step = None;
+        self.initial_origins = vec![];
+        self.origins = vec![];
+        self.history = vec![self.current_step.clone()];
+        self.previous_state = None;
+        self.branch = None;
+        self.ntm_tree = None;
+        self.selected_branch = None;
+    }
+
+
+    /// Rebuild the states of the graph from the current turing machine
+    pub fn build_graph(&mut self) {
         self.states_hash = HashMap::new();
         let mut pos: Pos2 = Pos2::ZERO;
         for (name, index) in self.turing.get_turing_machine().name_index_hashmap.iter() {
@@ -119,18 +339,196 @@ impl TuringApp {
             // increment position to avoid collision or huge amount of force at initialisation
             pos = (pos.to_vec2() + vec2(200.0, random_range(-100.0..100.0))).to_pos2();
         }
-        self.current_step = TuringExecutionStep::new(self.turing.turing_machine.k);
-        self.count = 0;
-        self.is_accepted = None;
     }
 
 
     /// Try to convert the graph to code. if impossible display error
-    pub fn apply_graph() {
+    pub fn apply_graph(&mut self) {
+        // the code is written from the compiled machine, where the macros are inlined
+        if !self.macros.is_empty() {
+            self.messages.push(format!(
+                "The machines run by '{}' lines were inlined in the code",
+                compose::RUN
+            ));
+        }
+        self.code = source::to_source(&self.turing.turing_machine);
+        if !self.tape_names.is_empty() {
+            self.code = source::tapes_comment(&self.tape_names) + "\n" + &self.code;
+        }
+        self.spans = source::spans(&self.code, &self.turing.turing_machine);
+        self.problems = analysis::analyze(&self.turing.turing_machine);
+    }
+
+
+    /// Load a bundled example with its suggested input
+    pub fn load_example(&mut self, example: &Example) {
+        self.code = example.code.to_string();
+        self.input = example.input.to_string();
+        self.messages = vec![];
+        self.compile();
+        self.update_input();
+    }
 
+
+    /// Load an imported machine: compile its code and restore its layout if any
+    pub fn load_imported(&mut self, imported: ImportedMachine) {
+        self.code = imported.code;
+        if let Some(input) = imported.input {
+            self.input = input;
+        }
+        self.compile();
+
+        // keep the imported layout instead of the organic one
+        if !imported.positions.is_empty() {
+            self.restore_positions(&imported.positions);
+            self.graph_rect = Rect::ZERO;
+        }
+        self.messages = imported.warnings;
+        self.update_input();
     }
 
 
+    /// Load an imported machine as an edit that can be undone
+    pub fn load_undoable(&mut self, label: &'static str, imported: ImportedMachine) {
+        let before = self.snapshot(label);
+        self.load_imported(imported);
+        self.push_undo(before);
+    }
+
+    /// Load a machine built elsewhere, its code being written from it, as an edit that can be undone
+    pub fn load_machine(&mut self, label: &'static str, tm: TuringMachine) {
+        // the current code, with its macros, can be brought back
+        let before = self.snapshot(label);
+        self.tape_names = vec![];
+        self.macros = vec![];
+        (self.turing, self.current_step) = TuringMachineExecutor::new(tm, String::new()).unwrap();
+        self.apply_graph();
+        self.messages = vec![];
+        self.compile();
+        self.input = String::new();
+        self.update_input();
+        self.push_undo(before);
+    }
+
+
+    /// Load the universal machine with the description of the current machine and a word as input
+    pub fn run_universal(&mut self) {
+        let input = universal::encode(&self.turing.turing_machine)
+            .and_then(|description| universal::universal_input(&description, &self.universal_word));
+        let Some(example) = EXAMPLES.iter().find(|e| e.id == universal::UNIVERSAL) else {
+            return;
+        };
+
+        match input {
+            Ok(input) => {
+                self.universal_error = None;
+                self.load_example(example);
+                self.input = input;
+                self.update_input();
+            }
+            Err(e) => self.universal_error = Some(e),
+        }
+    }
+
+    /// Code of the current machine cleaned up, keeping the names of the ribbons and the saved tests.
+    /// Refused when the code runs other machines, the other comments are dropped.
+    pub fn cleaned_code(&self, cleanup: Cleanup) -> Result<cleanup::Cleaned, String> {
+        // the code is rebuilt from the compiled machine, where the macros are inlined
+        if !compose::expand(&self.code)?.macros.is_empty() {
+            return Err(format!(
+                "The machines run by '{}' lines would be inlined, remove them before cleaning up",
+                compose::RUN
+            ));
+        }
+
+        let mut cleaned = cleanup.apply(&self.turing.turing_machine)?;
+        if !self.tape_names.is_empty() {
+            cleaned.code = source::tapes_comment(&self.tape_names) + "\n" + &cleaned.code;
+        }
+        for (input, accepted) in source::tests(&self.code) {
+            cleaned.code += &format!("\n{}", source::test_comment(&input, accepted));
+        }
+
+        let comments = |code: &str| code.lines().filter(|l| l.contains("//")).count();
+        let dropped = comments(&self.code).saturating_sub(comments(&cleaned.code));
+        if dropped > 0 {
+            cleaned.summary += &format!(", {} comments not kept", dropped);
+        }
+        Ok(cleaned)
+    }
+
+    /// Replace the machine by its cleaned up version, as an edit that can be undone
+    pub fn apply_cleanup(&mut self, cleanup: Cleanup) {
+        let cleaned = match self.cleaned_code(cleanup) {
+            Ok(cleaned) => cleaned,
+            Err(e) => {
+                self.messages = vec![e];
+                return;
+            }
+        };
+
+        // the states keep the place of the ones they come from
+        let positions: HashMap<String, Pos2> = cleaned
+            .origins
+            .iter()
+            .filter_map(|(name, old)| self.states_hash.get(old).map(|s| (name.clone(), s.position)))
+            .collect();
+        let before = self.snapshot(cleanup.name());
+
+        self.code = cleaned.code;
+        self.messages = vec![cleaned.summary];
+        self.compile();
+        self.restore_positions(&positions);
+        self.push_undo(before);
+    }
+
+    // the machine as it is before an edit
+    fn snapshot(&self, label: &'static str) -> Snapshot {
+        Snapshot { label, code: self.code.clone(), positions: self.positions(), after: String::new() }
+    }
+
+    // remember the machine before an edit once it is done
+    fn push_undo(&mut self, mut before: Snapshot) {
+        before.after = self.code.clone();
+        self.undo.push(before);
+    }
+
+    /// Last edit that can be undone, none once the code was changed after it
+    pub fn last_edit(&self) -> Option<&Snapshot> {
+        self.undo.last().filter(|s| s.after == self.code)
+    }
+
+    /// Go back to the machine before the last edit, unless the code was changed since
+    pub fn undo(&mut self) {
+        if self.last_edit().is_none() {
+            // undoing would throw away what was typed after the edit
+            self.undo.clear();
+            return;
+        }
+        let Some(snapshot) = self.undo.pop() else {
+            return;
+        };
+        self.code = snapshot.code;
+        self.messages = vec![];
+        self.compile();
+        self.restore_positions(&snapshot.positions);
+    }
+
+    // position of every state of the graph, by name
+    fn positions(&self) -> HashMap<String, Pos2> {
+        self.states_hash.values().map(|s| (s.name.clone(), s.position)).collect()
+    }
+
+    // place the states of the graph, instead of the organic layout
+    fn restore_positions(&mut self, positions: &HashMap<String, Pos2>) {
+        for state in self.states_hash.values_mut() {
+            if let Some(pos) = positions.get(&state.name) {
+                state.position = *pos;
+            }
+        }
+        self.organic_layout = false;
+    }
+
     /// Update the input string 
     /// 
     /// TODO lock the graph to prevent modification during execution
@@ -140,25 +538,390 @@ impl TuringApp {
             TuringMachineExecutor::new(tm, self.input.clone()).unwrap();
         self.count = 0;
         self.is_accepted = None;
+        self.initial_step = None;
+        self.initial_origins = vec![];
+        self.origins = vec![];
+        self.previous_step = None;
+        self.history = vec![self.current_step.clone()];
+        self.previous_state = None;
+        self.branch = None;
     }
 
-    /// Go to next state by following available transition if exist
+    /// Compare the current machine with the second one on every word up to the chosen length,
+    /// loading the first counterexample into the input
+    pub fn check_equivalence(&mut self) {
+        let report = compose::expand(&self.second_code)
+            .and_then(|composed| source::parse(&composed.code))
+            .and_then(|second| {
+                let first = &self.turing.turing_machine;
+                if self.equivalence_alphabet.trim().is_empty() {
+                    self.equivalence_alphabet = equivalence::alphabet(first, &second);
+                }
+                let alphabet: Vec<char> = self.equivalence_alphabet.chars().filter(|c| !c.is_whitespace()).collect();
+                Ok(equivalence::Checker::new(
+                    first,
+                    &second,
+                    &alphabet,
+                    self.equivalence_length,
+                    self.equivalence_budget,
+                    self.equivalence_outputs,
+                ))
+            });
+
+        // the words are compared a slice per frame, see work
+        self.equivalence_report = Some(report);
+    }
+
+    /// Check the machine against the chosen oracle on random inputs
+    pub fn fuzz(&mut self) {
+        let oracle = match self.fuzz_oracle {
+            fuzz::OracleKind::Regex => fuzz::Oracle::regex(&self.fuzz_regex),
+            fuzz::OracleKind::List => Ok(fuzz::Oracle::accepted(&self.fuzz_list)),
+            fuzz::OracleKind::Machine => compose::expand(&self.second_code)
+                .and_then(|composed| source::parse(&composed.code))
+                .map(fuzz::Oracle::Machine),
+        };
+
+        let tm = &self.turing.turing_machine;
+        if self.fuzz_alphabet.trim().is_empty() {
+            self.fuzz_alphabet = equivalence::alphabet(tm, tm);
+        }
+        let alphabet: Vec<char> = self.fuzz_alphabet.chars().filter(|c| !c.is_whitespace()).collect();
+
+        // the inputs are run a slice per frame, see work
+        self.fuzz_campaign = Some(oracle.and_then(|oracle| {
+            fuzz::Fuzzer::new(tm, oracle, &alphabet, self.fuzz_runs, self.fuzz_length, self.fuzz_budget)
+        }));
+    }
+
+    /// Save an input and its expected verdict as a test in the code,
+    /// unless the code already has a test on this input
+    pub fn save_test(&mut self, input: &str, accepted: bool) {
+        let saved = source::tests(&self.code).into_iter().find(|(i, _)| i == input);
+        match saved {
+            Some((_, verdict)) if verdict == accepted => return,
+            Some(_) => {
+                self.messages = vec![format!("A test already expects the opposite verdict on '{}'", input)];
+                return;
+            }
+            None => {}
+        }
+        if !self.code.is_empty() && !self.code.ends_with('\n') {
+            self.code.push('\n');
+        }
+        self.code += &source::test_comment(input, accepted);
+        self.code.push('\n');
+    }
+
+    /// Run the tests saved in the code
+    pub fn run_tests(&mut self) {
+        let tests = source::tests(&self.code);
+        // the tests are run a slice per frame, see work
+        self.test_failures = Some(Ok(fuzz::Tester::new(&self.turing.turing_machine, tests, self.fuzz_budget)));
+    }
+
+    /// Restart the execution, from the ribbons edited by the user if any
+    pub fn reset(&mut self) {
+        self.origins = self.initial_origins.clone();
+        if let Some(branch) = &self.branch {
+            self.current_step = branch[0].1.clone();
+            self.history = branch.iter().map(|(_, step)| step.clone()).collect();
+            self.count = 0;
+            self.is_accepted = None;
+            self.previous_step = None;
+            self.previous_state = None;
+            return;
+        }
+
+        let Some(step) = self.initial_step.clone() else {
+            self.update_input();
+            return;
+        };
+
+        self.input = step.read_ribbon.chars_vec.iter().collect();
+        let tm = self.turing.turing_machine.clone();
+        (self.turing, self.current_step) =
+            TuringMachineExecutor::new(tm, self.input.clone()).unwrap();
+
+        self.turing.read_ribbon = step.read_ribbon.clone();
+        self.turing.write_ribbons = step.write_ribbons.clone();
+        self.current_step.read_ribbon = step.read_ribbon;
+        self.current_step.write_ribbons = step.write_ribbons;
+        self.count = 0;
+        self.is_accepted = None;
+        self.previous_step = None;
+        self.history = vec![self.current_step.clone()];
+        self.previous_state = None;
+    }
+
+    /// Write a symbol on a cell of the displayed ribbons, 0 being the read ribbon,
+    /// and restart the execution from this configuration
+    pub fn write_cell(&mut self, ribbon: usize, cell: i32, symbol: &str) {
+        let (chars, pointer) = tape::ribbon_mut(&mut self.current_step, ribbon);
+        if let Err(e) = tape::write(chars, pointer, cell, symbol) {
+            self.messages = vec![e];
+            return;
+        }
+        self.restart_from_ribbons(ribbon, cell);
+    }
+
+    /// Place the head of a ribbon on a cell and restart the execution from this configuration
+    pub fn move_head(&mut self, ribbon: usize, cell: i32) {
+        let (chars, pointer) = tape::ribbon_mut(&mut self.current_step, ribbon);
+        tape::move_head(chars, pointer, cell);
+        self.restart_from_ribbons(ribbon, cell);
+    }
+
+    // keep the displayed ribbons as the start of the next executions,
+    // a ribbon having grown to the left when a cell before its start was edited
+    fn restart_from_ribbons(&mut self, ribbon: usize, cell: i32) {
+        self.origins.resize(self.current_step.write_ribbons.len() + 1, 0);
+        self.origins[ribbon] += (-cell).max(0) as usize;
+        self.branch = None;
+        self.initial_step = Some(self.current_step.clone());
+        self.initial_origins = self.origins.clone();
+        self.reset();
+    }
+
+    /// Cells added before the first cell of a ribbon, see `origins`
+    pub fn origin(&self, ribbon: usize) -> usize {
+        self.origins.get(ribbon).copied().unwrap_or(0)
+    }
+
+    /// Go to next state by following available transition if exist,
+    /// or the next configuration of the branch being replayed
     pub fn next(&mut self) {
-        match self.turing.as_iter().next() {
+        let state = self.state();
+        let next = match &self.branch {
+            Some(branch) => branch.get(self.count + 1).map(|(_, step)| step.clone()),
+            None => self.turing.as_iter().next(),
+        };
+
+        match next {
             Some(x) => {
-                self.current_step = x;
+                self.origins.resize(x.write_ribbons.len() + 1, 0);
+                for (ribbon, origin) in self.origins.iter_mut().enumerate() {
+                    *origin += tape::grown_left(&self.current_step, &x, ribbon);
+                }
+
+                // kept whole, the cells of its heads are only found when drawing
+                self.previous_step = Some(std::mem::replace(&mut self.current_step, x));
+                self.previous_state = Some(state);
+                self.animation_start = None;
                 self.count += 1;
+
+                // record the steps not recorded yet, up to a limit
+                if self.history.len() <= self.count && self.history.len() < Constant::MAX_HISTORY {
+                    self.history.push(self.current_step.clone());
+                }
             }
             None => {
-                self.is_accepted = Some(
-                    self.turing
-                        .turing_machine
-                        .get_state(self.turing.get_state_pointer())
-                        .is_final,
-                )
+                self.is_accepted = Some(self.turing.turing_machine.get_state(state).is_final)
+            }
+        }
+    }
+
+    /// Current state, of the branch being replayed if any
+    pub fn state(&self) -> u8 {
+        match &self.branch {
+            Some(branch) => branch[self.count.min(branch.len() - 1)].0,
+            None => self.turing.get_state_pointer(),
+        }
+    }
+
+    /// Explore the computation tree from the initial configuration
+    pub fn explore(&mut self) {
+        self.branch = None;
+        self.reset();
+        self.ntm_tree = Some(ntm::explore(
+            &self.turing.turing_machine,
+            &self.current_step,
+            self.ntm_depth,
+            self.ntm_nodes,
+        ));
+        self.selected_branch = None;
+    }
+
+    /// Replay the branch of the computation tree leading to a node through the rubans and the graph
+    pub fn replay_branch(&mut self, node: usize) {
+        let Some(tree) = &self.ntm_tree else {
+            return;
+        };
+        self.branch = Some(tree.branch(node));
+        self.selected_branch = Some(node);
+        self.running = false;
+        self.reset();
+    }
+
+    /// Stop replaying a branch and go back to the execution of the machine
+    pub fn leave_branch(&mut self) {
+        self.branch = None;
+        self.selected_branch = None;
+        self.reset();
+    }
+
+    /// Replay the execution from the start up to a recorded step, keeping the recorded history
+    pub fn goto_step(&mut self, step: usize) {
+        let history = std::mem::take(&mut self.history);
+        self.running = false;
+        self.reset();
+        for _ in 0..step {
+            self.next();
+        }
+        self.history = history;
+        self.previous_step = None;
+    }
+
+    /// Line of the code defining the transition just taken
+    pub fn active_rule(&self) -> Option<usize> {
+        let from = self.previous_state?;
+        let id = self
+            .turing
+            .turing_machine
+            .get_state(from)
+            .transitions
+            .iter()
+            .position(|t| *t == self.current_step.transition_taken)?;
+        self.spans.rules.get(&(from, id)).copied()
+    }
+
+    /// Select a state of the graph and move the editor cursor to its rules
+    pub fn select_state(&mut self, index: u8) {
+        self.selected_node = Some(index);
+        self.selected_transition = None;
+        self.code_cursor = self.spans.states.get(&index).map(|(first, _)| *first);
+    }
+
+    /// Select a rule of the graph and move the editor cursor to its line
+    pub fn select_rule(&mut self, from: u8, id: u8) {
+        let to = self.turing.turing_machine.get_state(from).get_transition(id).index_to_state;
+        self.selected_transition = Some((from, to));
+        self.selected_node = None;
+        self.code_cursor = self.spans.rules.get(&(from, id as usize)).copied();
+    }
+
+    /// Select and center the element of the graph under the editor cursor: the rule of the line,
+    /// or else the state named by the word under the cursor
+    pub fn select_from_code(&mut self, line: usize, word: &str) {
+        let rule = self.spans.rules.iter().find(|(_, l)| **l == line).map(|(r, _)| *r);
+        if let Some((from, id)) = rule {
+            let to = self.turing.turing_machine.get_state(from).get_transition(id as u8).index_to_state;
+            self.selected_transition = Some((from, to));
+            self.selected_node = None;
+            if let (Some(a), Some(b)) = (self.states_hash.get(&from), self.states_hash.get(&to)) {
+                self.center_graph(a.position.lerp(b.position, 0.5));
+            }
+            return;
+        }
+
+        if let Some(index) = self.turing.turing_machine.name_index_hashmap.get(word).copied() {
+            self.selected_node = Some(index);
+            self.selected_transition = None;
+            if let Some(state) = self.states_hash.get(&index) {
+                self.center_graph(state.position);
             }
         }
     }
+
+    // move the view of the graph to center a position, keeping its zoom
+    fn center_graph(&mut self, pos: Pos2) {
+        let size = if self.graph_rect.is_positive() { self.graph_rect.size() } else { vec2(600.0, 400.0) };
+        self.graph_rect = Rect::from_center_size(pos, size);
+    }
+
+    /// Profile the current machine, its runs going on a slice per frame
+    pub fn profile(&mut self) {
+        let tm = &self.turing.turing_machine;
+        let inputs = if self.profile_random {
+            if self.profile_alphabet.trim().is_empty() {
+                self.profile_alphabet = equivalence::alphabet(tm, tm);
+            }
+            Inputs::Random { alphabet: self.profile_alphabet.clone(), samples: self.profile_count }
+        } else {
+            Inputs::Pattern(self.profile_pattern.clone())
+        };
+        match profile::Profiler::new(tm, &inputs, self.profile_max, self.profile_budget) {
+            Ok(profiler) => {
+                self.profiler = Some(profiler);
+                self.profile_error = None;
+            }
+            Err(e) => self.profile_error = Some(e),
+        }
+    }
+
+    /// Advance the long computations going on by a slice, returning whether some are left
+    pub fn work(&mut self) -> bool {
+        let mut left = false;
+        if let Some(Ok(enumerator)) = &mut self.beaver_result {
+            left |= !enumerator.advance(Constant::WORK_PER_FRAME);
+        }
+
+        match &mut self.profiler {
+            Some(profiler) if !profiler.done() => match profiler.advance(Constant::WORK_PER_FRAME) {
+                Ok(done) => left |= !done,
+                Err(e) => {
+                    profiler.stop();
+                    self.profile_error = Some(e);
+                }
+            },
+            _ => {}
+        }
+
+        match &mut self.equivalence_report {
+            Some(Ok(checker)) if !checker.done() => match checker.advance(Constant::WORK_PER_FRAME) {
+                Ok(false) => left = true,
+                // load the counterexample as input once found
+                Ok(true) => {
+                    if let Some(input) = checker.report.counterexample.as_ref().map(|c| c.input.clone()) {
+                        self.input = input;
+                        self.update_input();
+                    }
+                }
+                Err(e) => self.equivalence_report = Some(Err(e)),
+            },
+            _ => {}
+        }
+
+        match &mut self.fuzz_campaign {
+            Some(Ok(fuzzer)) if !fuzzer.done() => match fuzzer.advance(Constant::WORK_PER_FRAME) {
+                Ok(done) => left |= !done,
+                Err(e) => self.fuzz_campaign = Some(Err(e)),
+            },
+            _ => {}
+        }
+
+        match &mut self.test_failures {
+            Some(Ok(tester)) if !tester.done() => match tester.advance(Constant::WORK_PER_FRAME) {
+                Ok(done) => left |= !done,
+                Err(e) => self.test_failures = Some(Err(e)),
+            },
+            _ => {}
+        }
+        left
+    }
+
+    /// Execute the steps due at the run speed, called every frame
+    pub fn run(&mut self, time: f64) {
+        if !self.running {
+            return;
+        }
+
+        if self.speed >= Constant::MAX_SPEED {
+            for _ in 0..Constant::MAX_STEPS_PER_FRAME {
+                self.next();
+                if self.is_accepted.is_some() {
+                    break;
+                }
+            }
+        } else if time - self.last_run_time >= 1.0 / self.speed as f64 {
+            self.last_run_time = time;
+            self.next();
+        }
+
+        if self.is_accepted.is_some() {
+            self.running = false;
+        }
+    }
 }
 
 
@@ -168,6 +931,14 @@ impl eframe::App for TuringApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         install_image_loaders(ctx);
 
+        self.run(ctx.input(|i| i.time));
+        if self.running {
+            ctx.request_repaint();
+        }
+        if self.work() {
+            ctx.request_repaint();
+        }
+
         ui::show(self, ctx);
     }
 }
@@ -0,0 +1,457 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use egui::{Pos2, pos2};
+use turingrs::turing_state::TuringDirection;
+
+use crate::{
+    format::ImportedMachine,
+    source::{self, ACCEPTING, BLANK},
+};
+
+/// Distance between the columns and the rows of the generated layout
+const SPACING: f32 = 150.0;
+/// Most states a generated machine can have, state indices being bytes
+const MAX_STATES: usize = 255;
+
+/// A deterministic automaton, its initial state being the first one.
+/// A missing transition refuses the input.
+#[derive(Clone, Debug)]
+pub struct Dfa {
+    pub names: Vec<String>,
+    pub alphabet: Vec<char>,
+    pub delta: Vec<HashMap<char, usize>>,
+    pub accepting: Vec<bool>,
+}
+
+impl Dfa {
+    /// Parse a transition table: a header of symbols, then a line per state with its
+    /// targets in the order of the header, `-` for none. `>` marks the initial state,
+    /// the first one by default, and `*` the accepting states.
+    ///
+    /// ```text
+    ///      a    b
+    /// >q0  q1   q0
+    /// *q1  q1   -
+    /// ```
+    pub fn from_table(table: &str) -> Result<Dfa, String> {
+        let mut lines = table
+            .lines()
+            .map(|l| l.split("//").next().unwrap_or("").trim())
+            .filter(|l| !l.is_empty());
+
+        let header = lines.next().ok_or_else(|| String::from("The table is empty"))?;
+        let mut alphabet = vec![];
+        for symbol in header.split_whitespace() {
+            let mut chars = symbol.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c != BLANK && source::is_symbol(c) && !alphabet.contains(&c) => alphabet.push(c),
+                _ => return Err(format!("Invalid symbol '{}' in the header", symbol)),
+            }
+        }
+
+        let mut rows = vec![];
+        let mut initial = None;
+        for line in lines {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.len() != alphabet.len() + 1 {
+                return Err(format!("Line '{}' should have a state and {} targets", line, alphabet.len()));
+            }
+
+            let name = words[0].trim_start_matches(['>', '*']);
+            let marks = &words[0][..words[0].len() - name.len()];
+            if !is_name(name) {
+                return Err(format!("Invalid state name '{}'", name));
+            }
+            if marks.contains('>') && initial.replace(rows.len()).is_some() {
+                return Err(String::from("Several states are marked initial with '>'"));
+            }
+            rows.push((name.to_string(), marks.contains('*'), words[1..].to_vec()));
+        }
+        if rows.is_empty() {
+            return Err(String::from("The table has no state"));
+        }
+
+        // the initial state first
+        rows.swap(0, initial.unwrap_or(0));
+        let names: Vec<String> = rows.iter().map(|(name, ..)| name.clone()).collect();
+        let index: HashMap<&str, usize> = names.iter().enumerate().map(|(i, n)| (n.as_str(), i)).collect();
+        if index.len() < names.len() {
+            return Err(String::from("A state is defined twice"));
+        }
+
+        let mut delta = vec![];
+        for (name, _, targets) in rows.iter() {
+            let mut transitions = HashMap::new();
+            for (symbol, target) in alphabet.iter().zip(targets.iter()) {
+                if *target == "-" {
+                    continue;
+                }
+                let to = index
+                    .get(target)
+                    .ok_or_else(|| format!("Unknown state '{}' in the line of '{}'", target, name))?;
+                transitions.insert(*symbol, *to);
+            }
+            delta.push(transitions);
+        }
+
+        let accepting = rows.iter().map(|(_, accepting, _)| *accepting).collect();
+        Ok(Dfa { names, alphabet, delta, accepting })
+    }
+
+    /// Minimal automaton of a regular expression over the symbols it uses.
+    ///
+    /// Supports `|`, `*`, `+`, `?`, parentheses, `ε` for the empty word and `\` to escape.
+    pub fn from_regex(pattern: &str) -> Result<Dfa, String> {
+        let chars: Vec<char> = pattern.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut nfa = Nfa::default();
+        let mut pos = 0;
+        let (start, end) = nfa.alternation(&chars, &mut pos)?;
+        if pos < chars.len() {
+            return Err(format!("Unexpected '{}' in the regular expression", chars[pos]));
+        }
+
+        let alphabet: Vec<char> = nfa
+            .nodes
+            .iter()
+            .flat_map(|n| n.edges.iter().map(|(c, _)| *c))
+            .collect::<BTreeSet<char>>()
+            .into_iter()
+            .collect();
+
+        // subset construction, the sets being numbered in order of discovery
+        let first = nfa.closure([start].into());
+        let mut sets = vec![first.clone()];
+        let mut index = HashMap::from([(first, 0)]);
+        let mut delta = vec![];
+        let mut i = 0;
+        while i < sets.len() {
+            let mut transitions = HashMap::new();
+            for c in alphabet.iter() {
+                let next: BTreeSet<usize> = sets[i]
+                    .iter()
+                    .flat_map(|n| nfa.nodes[*n].edges.iter().filter(|(e, _)| e == c).map(|(_, to)| *to))
+                    .collect();
+                if next.is_empty() {
+                    continue;
+                }
+                let next = nfa.closure(next);
+                let to = *index.entry(next.clone()).or_insert_with(|| {
+                    sets.push(next);
+                    sets.len() - 1
+                });
+                transitions.insert(*c, to);
+            }
+            delta.push(transitions);
+            i += 1;
+        }
+
+        let accepting = sets.iter().map(|s| s.contains(&end)).collect();
+        let names = (0..sets.len()).map(|i| format!("q{}", i)).collect();
+        Ok(Dfa { names, alphabet, delta, accepting }.minimize())
+    }
+
+    /// Merge the states accepting the same words, the missing transitions going to a refusing sink
+    pub fn minimize(&self) -> Dfa {
+        let sink = usize::MAX;
+        let mut class: Vec<usize> = self.accepting.iter().map(|a| *a as usize).collect();
+
+        loop {
+            let signatures: Vec<(usize, Vec<usize>)> = (0..self.names.len())
+                .map(|s| {
+                    let targets = self.alphabet.iter().map(|c| self.delta[s].get(c).map_or(sink, |t| class[*t]));
+                    (class[s], targets.collect())
+                })
+                .collect();
+            let mut numbers = HashMap::new();
+            let refined: Vec<usize> = signatures
+                .into_iter()
+                .map(|signature| {
+                    let count = numbers.len();
+                    *numbers.entry(signature).or_insert(count)
+                })
+                .collect();
+
+            let stable = numbers.len() == class.iter().collect::<BTreeSet<_>>().len();
+            class = refined;
+            if stable {
+                break;
+            }
+        }
+
+        // classes numbered from the initial state, in order of discovery
+        let mut order = HashMap::from([(class[0], 0)]);
+        let mut members = vec![0];
+        let mut queue = VecDeque::from([0]);
+        while let Some(s) = queue.pop_front() {
+            for c in self.alphabet.iter() {
+                if let Some(t) = self.delta[s].get(c) {
+                    if !order.contains_key(&class[*t]) {
+                        order.insert(class[*t], members.len());
+                        members.push(*t);
+                        queue.push_back(*t);
+                    }
+                }
+            }
+        }
+
+        let delta = members
+            .iter()
+            .map(|s| self.delta[*s].iter().map(|(c, t)| (*c, order[&class[*t]])).collect())
+            .collect();
+        Dfa {
+            names: (0..members.len()).map(|i| format!("q{}", i)).collect(),
+            alphabet: self.alphabet.clone(),
+            delta,
+            accepting: members.iter().map(|s| self.accepting[*s]).collect(),
+        }
+    }
+
+    /// Machine reading its input from left to right as the automaton, going to an accepting
+    /// state on the blank after the input if the automaton accepts it.
+    /// States are laid out from left to right by distance to the initial state.
+    pub fn to_machine(&self) -> Result<ImportedMachine, String> {
+        if self.names.len() >= MAX_STATES {
+            return Err(format!("The automaton has {} states, at most {} fit", self.names.len(), MAX_STATES - 1));
+        }
+
+        let mut accept = String::from("accept");
+        while self.names.contains(&accept) {
+            accept.push('_');
+        }
+
+        let mut lines = vec![format!("{}: {};", ACCEPTING, accept), String::new()];
+        for (s, name) in self.names.iter().enumerate() {
+            for c in self.alphabet.iter().filter(|c| self.delta[s].contains_key(c)) {
+                let body = source::format_rule(&[*c], &TuringDirection::Right, &[]);
+                lines.push(source::rule_line(name, &body, &self.names[self.delta[s][c]]));
+            }
+            if self.accepting[s] {
+                let body = source::format_rule(&[BLANK], &TuringDirection::None, &[]);
+                lines.push(source::rule_line(name, &body, &accept));
+            }
+        }
+
+        // columns by distance to the initial state, the accepting state last
+        let mut depth = vec![usize::MAX; self.names.len()];
+        depth[0] = 0;
+        let mut queue = VecDeque::from([0]);
+        while let Some(s) = queue.pop_front() {
+            for c in self.alphabet.iter() {
+                if let Some(t) = self.delta[s].get(c) {
+                    if depth[*t] == usize::MAX {
+                        depth[*t] = depth[s] + 1;
+                        queue.push_back(*t);
+                    }
+                }
+            }
+        }
+        let last = depth.iter().filter(|d| **d != usize::MAX).max().copied().unwrap_or(0);
+        // states the initial one can not reach go in a column of their own
+        for d in depth.iter_mut().filter(|d| **d == usize::MAX) {
+            *d = last + 1;
+        }
+
+        let mut rows: HashMap<usize, usize> = HashMap::new();
+        let mut positions: HashMap<String, Pos2> = HashMap::new();
+        for (name, column) in self.names.iter().zip(depth.iter()) {
+            let row = rows.entry(*column).or_insert(0);
+            positions.insert(name.clone(), pos2(*column as f32 * SPACING, *row as f32 * SPACING));
+            *row += 1;
+        }
+        let column = depth.iter().max().copied().unwrap_or(0) + 1;
+        positions.insert(accept, pos2(column as f32 * SPACING, 0.0));
+
+        let code = lines.join("\n");
+        // make sure the rules follow the syntax before loading anything
+        source::parse(&code)?;
+
+        Ok(ImportedMachine {
+            code,
+            input: Some(String::new()),
+            positions,
+            warnings: vec![format!(
+                "Decider of an automaton of {} states over {{{}}}",
+                self.names.len(),
+                self.alphabet.iter().map(char::to_string).collect::<Vec<_>>().join(", ")
+            )],
+        })
+    }
+}
+
+// whether a state name can be written in the code
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+// nondeterministic automaton with empty transitions, built from a regular expression
+#[derive(Default)]
+struct Nfa {
+    nodes: Vec<Node>,
+}
+
+#[derive(Default)]
+struct Node {
+    empty: Vec<usize>,
+    edges: Vec<(char, usize)>,
+}
+
+impl Nfa {
+    fn node(&mut self) -> usize {
+        self.nodes.push(Node::default());
+        self.nodes.len() - 1
+    }
+
+    fn empty(&mut self, from: usize, to: usize) {
+        self.nodes[from].empty.push(to);
+    }
+
+    // alternatives separated by '|', returning the first and last node of the fragment
+    fn alternation(&mut self, chars: &[char], pos: &mut usize) -> Result<(usize, usize), String> {
+        let mut branches = vec![self.concatenation(chars, pos)?];
+        while chars.get(*pos) == Some(&'|') {
+            *pos += 1;
+            branches.push(self.concatenation(chars, pos)?);
+        }
+        if branches.len() == 1 {
+            return Ok(branches[0]);
+        }
+
+        let (start, end) = (self.node(), self.node());
+        for (first, last) in branches {
+            self.empty(start, first);
+            self.empty(last, end);
+        }
+        Ok((start, end))
+    }
+
+    fn concatenation(&mut self, chars: &[char], pos: &mut usize) -> Result<(usize, usize), String> {
+        let start = self.node();
+        let mut end = start;
+        while let Some(c) = chars.get(*pos) {
+            if *c == '|' || *c == ')' {
+                break;
+            }
+            let (first, last) = self.repetition(chars, pos)?;
+            self.empty(end, first);
+            end = last;
+        }
+        Ok((start, end))
+    }
+
+    fn repetition(&mut self, chars: &[char], pos: &mut usize) -> Result<(usize, usize), String> {
+        let (mut first, mut last) = self.atom(chars, pos)?;
+        while let Some(op @ ('*' | '+' | '?')) = chars.get(*pos) {
+            *pos += 1;
+            let (start, end) = (self.node(), self.node());
+            self.empty(start, first);
+            self.empty(last, end);
+            if *op != '+' {
+                self.empty(start, end);
+            }
+            if *op != '?' {
+                self.empty(last, first);
+            }
+            (first, last) = (start, end);
+        }
+        Ok((first, last))
+    }
+
+    fn atom(&mut self, chars: &[char], pos: &mut usize) -> Result<(usize, usize), String> {
+        let c = chars[*pos];
+        *pos += 1;
+        let symbol = match c {
+            '(' => {
+                let group = self.alternation(chars, pos)?;
+                if chars.get(*pos) != Some(&')') {
+                    return Err(String::from("Missing ')' in the regular expression"));
+                }
+                *pos += 1;
+                return Ok(group);
+            }
+            'ε' => {
+                let node = self.node();
+                return Ok((node, node));
+            }
+            '*' | '+' | '?' => return Err(format!("Nothing to repeat before '{}'", c)),
+            '\\' => *chars.get(*pos).ok_or_else(|| String::from("Nothing to escape at the end"))?,
+            c => c,
+        };
+        if c == '\\' {
+            *pos += 1;
+        }
+        if symbol == BLANK {
+            return Err(format!("The blank '{}' can not be in an input", BLANK));
+        }
+        if !source::is_symbol(symbol) {
+            return Err(format!("'{}' can not be a symbol of a rule", symbol));
+        }
+
+        let (start, end) = (self.node(), self.node());
+        self.nodes[start].edges.push((symbol, end));
+        Ok((start, end))
+    }
+
+    // nodes reachable with empty transitions
+    fn closure(&self, nodes: BTreeSet<usize>) -> BTreeSet<usize> {
+        let mut closure = nodes.clone();
+        let mut stack: Vec<usize> = nodes.into_iter().collect();
+        while let Some(n) = stack.pop() {
+            for to in self.nodes[n].empty.iter() {
+                if closure.insert(*to) {
+                    stack.push(*to);
+                }
+            }
+        }
+        closure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execute::{self, Outcome};
+
+    fn accepts(dfa: &Dfa, input: &str) -> bool {
+        let machine = source::parse(&dfa.to_machine().unwrap().code).unwrap();
+        execute::run(&machine, input, 100).unwrap().outcome == Outcome::Accepted
+    }
+
+    #[test]
+    fn regex_machines_decide_the_language() {
+        for pattern in ["(ab)*c|a+", "a?b*", "(a|b)*abb", "ε|ba"] {
+            let dfa = Dfa::from_regex(pattern).unwrap();
+            let reference = regex::Regex::new(&format!("^(?:{})$", pattern.replace('ε', ""))).unwrap();
+            for input in execute::words(&['a', 'b', 'c'], 5) {
+                assert_eq!(accepts(&dfa, &input), reference.is_match(&input), "{} on '{}'", pattern, input);
+            }
+        }
+    }
+
+    #[test]
+    fn regex_automata_are_minimal() {
+        assert_eq!(Dfa::from_regex("(a|b)*abb").unwrap().names.len(), 4);
+        assert_eq!(Dfa::from_regex("a*a*a*").unwrap().names.len(), 1);
+    }
+
+    #[test]
+    fn table_machines_decide_the_language() {
+        // even number of a
+        let dfa = Dfa::from_table("a b\n*even odd even\nodd even odd").unwrap();
+        for input in execute::words(&['a', 'b'], 5) {
+            let even = input.chars().filter(|c| *c == 'a').count() % 2 == 0;
+            assert_eq!(accepts(&dfa, &input), even, "on '{}'", input);
+        }
+
+        let dfa = Dfa::from_table("x\nstart end\n>*end -").unwrap();
+        assert_eq!(dfa.names, ["end", "start"]);
+        assert!(Dfa::from_table("a\nq0 q1").is_err());
+    }
+
+    #[test]
+    fn separators_of_rules_are_not_symbols() {
+        for c in source::SEPARATORS {
+            assert!(Dfa::from_regex(&format!("a\\{}", c)).is_err(), "'{}'", c);
+            assert!(Dfa::from_table(&format!("{}\n*q q", c)).is_err(), "'{}'", c);
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::{HashSet, VecDeque};
+
+use turingrs::{turing_machine::TuringMachine, turing_state::TuringDirection};
+
+use crate::source::{self, ACCEPTING, BLANK};
+
+/// Largest number of states and symbols enumerated, the tree growing too fast above
+pub const MAX_STATES: usize = 6;
+pub const MAX_SYMBOLS: usize = 4;
+/// Largest limits of steps per machine and of machines
+pub const MAX_STEPS: usize = 100_000;
+pub const MAX_MACHINES: usize = 10_000_000;
+
+/// Name of the state the machines halt in
+pub const HALT: &str = "H";
+
+/// What a machine does on a state and a symbol
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Action {
+    pub write: u8,
+    pub right: bool,
+    /// Next state, None to halt
+    pub next: Option<u8>,
+}
+
+/// A machine working on a single tape, its table indexed by state * symbols + symbol
+#[derive(Clone, Debug)]
+pub struct Beaver {
+    pub states: usize,
+    pub symbols: usize,
+    pub table: Vec<Option<Action>>,
+    /// Steps before halting on an empty tape, the halting one included
+    pub steps: usize,
+    /// Symbols other than the blank left on the tape
+    pub written: usize,
+}
+
+impl Beaver {
+    /// Code of the machine, its tape being the first write ribbon
+    pub fn to_source(&self) -> String {
+        let mut lines = vec![format!("{}: {};", ACCEPTING, HALT), String::new()];
+        for (i, action) in self.table.iter().enumerate() {
+            let Some(action) = action else { continue };
+            let direction = if action.right { TuringDirection::Right } else { TuringDirection::Left };
+            let body = source::format_rule(
+                &[BLANK, symbol(i % self.symbols)],
+                &TuringDirection::None,
+                &[(symbol(action.write as usize), direction)],
+            );
+            let next = action.next.map(state).unwrap_or_else(|| HALT.to_string());
+            lines.push(source::rule_line(&state((i / self.symbols) as u8), &body, &next));
+        }
+        lines.join("\n")
+    }
+
+    pub fn machine(&self) -> Result<TuringMachine, String> {
+        source::parse(&self.to_source())
+    }
+}
+
+// name of a state: A, B, C...
+fn state(index: u8) -> String {
+    char::from(b'A' + index).to_string()
+}
+
+// symbol of an index, 0 being the blank
+fn symbol(index: usize) -> char {
+    if index == 0 { BLANK } else { char::from_digit(index as u32, 10).unwrap_or('?') }
+}
+
+/// Machines enumerated and the best of them
+#[derive(Clone, Debug, Default)]
+pub struct Enumeration {
+    /// Machines run to the end, a machine being a leaf of the tree
+    pub machines: usize,
+    pub halted: usize,
+    /// Machines going back to a configuration seen before, up to a shift
+    pub cycles: usize,
+    /// Machines still running after the step limit
+    pub undecided: usize,
+    /// The limit of machines was reached before the end of the tree
+    pub truncated: bool,
+    pub by_steps: Vec<Beaver>,
+    pub by_written: Vec<Beaver>,
+}
+
+// how the run of a partial table ended
+enum End {
+    Halted { steps: usize, written: usize },
+    /// The table has no action for this state and symbol yet
+    Undefined { state: u8, symbol: u8 },
+    Cycle,
+    Timeout,
+}
+
+/// An enumeration going on, advanced a slice at a time to keep the interface responsive
+pub struct Enumerator {
+    states: usize,
+    symbols: usize,
+    max_steps: usize,
+    max_machines: usize,
+    keep: usize,
+    /// Tables left to run, the tree being explored depth first
+    stack: Vec<Vec<Option<Action>>>,
+    pub enumeration: Enumeration,
+}
+
+impl Enumerator {
+    /// Enumerate the machines of n states and m symbols in tree normal form.
+    ///
+    /// A table starts empty and is run on an empty tape: whenever the run reaches a missing
+    /// action, the table branches on every action, the states being numbered by first use
+    /// and the first action moving right to the second state. Only the tables a run can
+    /// reach are built, each one being kept once.
+    pub fn new(states: usize, symbols: usize, max_steps: usize, max_machines: usize, keep: usize) -> Result<Self, String> {
+        if !(1..=MAX_STATES).contains(&states) {
+            return Err(format!("The number of states must be between 1 and {}", MAX_STATES));
+        }
+        if !(2..=MAX_SYMBOLS).contains(&symbols) {
+            return Err(format!("The number of symbols must be between 2 and {}", MAX_SYMBOLS));
+        }
+        if max_steps > MAX_STEPS || max_machines > MAX_MACHINES {
+            return Err(format!("At most {} steps and {} machines can be run", MAX_STEPS, MAX_MACHINES));
+        }
+
+        Ok(Enumerator {
+            states,
+            symbols,
+            max_steps,
+            max_machines,
+            keep,
+            stack: vec![vec![None; states * symbols]],
+            enumeration: Enumeration::default(),
+        })
+    }
+
+    /// Whether every table was run or the limit of machines reached
+    pub fn done(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    /// Stop the enumeration, what was found so far being kept
+    pub fn stop(&mut self) {
+        if !self.done() {
+            self.enumeration.truncated = true;
+            self.stack.clear();
+        }
+    }
+
+    /// Run tables until about `work` steps were simulated, returning whether the enumeration is over
+    pub fn advance(&mut self, work: usize) -> bool {
+        let mut spent = 0;
+        while spent < work {
+            let Some(table) = self.stack.pop() else {
+                break;
+            };
+            if self.enumeration.machines >= self.max_machines {
+                self.stop();
+                break;
+            }
+
+            let (end, steps) = run(&table, self.symbols, self.max_steps);
+            // a table costs at least a step, even when it does not run
+            spent += steps.max(1);
+
+            let enumeration = &mut self.enumeration;
+            match end {
+                End::Halted { steps, written } => {
+                    enumeration.machines += 1;
+                    enumeration.halted += 1;
+                    let beaver = Beaver { states: self.states, symbols: self.symbols, table, steps, written };
+                    keep_best(&mut enumeration.by_steps, &beaver, self.keep, |b| (b.steps, b.written));
+                    keep_best(&mut enumeration.by_written, &beaver, self.keep, |b| (b.written, b.steps));
+                }
+                End::Cycle => {
+                    enumeration.machines += 1;
+                    enumeration.cycles += 1;
+                }
+                End::Timeout => {
+                    enumeration.machines += 1;
+                    enumeration.undecided += 1;
+                }
+                End::Undefined { state, symbol } => {
+                    self.stack.extend(children(&table, self.states, self.symbols, state, symbol));
+                }
+            }
+        }
+        self.done()
+    }
+}
+
+// tables defining the missing action in every way the normal form allows
+fn children(
+    table: &[Option<Action>],
+    states: usize,
+    symbols: usize,
+    state: u8,
+    symbol: u8,
+) -> Vec<Vec<Option<Action>>> {
+    let slot = state as usize * symbols + symbol as usize;
+    let with = |action: Action| {
+        let mut table = table.to_vec();
+        table[slot] = Some(action);
+        table
+    };
+
+    // halting writes a symbol, which direction does not matter
+    let mut children = vec![with(Action { write: 1, right: true, next: None })];
+
+    // the first action moves right to the second state, any other machine having a mirror or halting at once
+    if table.iter().all(Option::is_none) && states > 1 {
+        children.push(with(Action { write: 1, right: true, next: Some(1) }));
+        return children;
+    }
+
+    // a state can only be used after the ones before it
+    let used = table.iter().flatten().filter_map(|a| a.next).max().unwrap_or(0) as usize + 1;
+    for next in 0..(used + 1).min(states) {
+        for write in 0..symbols {
+            for right in [false, true] {
+                children.push(with(Action { write: write as u8, right, next: Some(next as u8) }));
+            }
+        }
+    }
+    children
+}
+
+// run a table on an empty tape, with the number of steps simulated
+fn run(table: &[Option<Action>], symbols: usize, max_steps: usize) -> (End, usize) {
+    let mut tape = VecDeque::from([0u8]);
+    let mut head = 0usize;
+    let mut state = 0u8;
+    let mut seen = HashSet::new();
+
+    for step in 0..max_steps {
+        let symbol = tape[head];
+        let Some(action) = table[state as usize * symbols + symbol as usize] else {
+            return (End::Undefined { state, symbol }, step);
+        };
+
+        tape[head] = action.write;
+        let Some(next) = action.next else {
+            let written = tape.iter().filter(|c| **c != 0).count();
+            return (End::Halted { steps: step + 1, written }, step + 1);
+        };
+        state = next;
+
+        if action.right {
+            head += 1;
+            if head == tape.len() {
+                tape.push_back(0);
+            }
+        } else if head == 0 {
+            tape.push_front(0);
+        } else {
+            head -= 1;
+        }
+
+        if !seen.insert(configuration(&tape, head, state)) {
+            return (End::Cycle, step + 1);
+        }
+    }
+    (End::Timeout, max_steps)
+}
+
+// configuration up to a shift of the tape: the state, the head from the first written cell and the written cells
+fn configuration(tape: &VecDeque<u8>, head: usize, state: u8) -> (u8, isize, Vec<u8>) {
+    let Some(first) = tape.iter().position(|c| *c != 0) else {
+        return (state, 0, vec![]);
+    };
+    let last = tape.iter().rposition(|c| *c != 0).unwrap_or(first);
+    (state, head as isize - first as isize, tape.range(first..=last).copied().collect())
+}
+
+// insert a machine among the best ones, sorted by decreasing score
+fn keep_best(best: &mut Vec<Beaver>, beaver: &Beaver, keep: usize, score: impl Fn(&Beaver) -> (usize, usize)) {
+    if best.len() >= keep && best.last().is_some_and(|last| score(last) >= score(beaver)) {
+        return;
+    }
+    let index = best.partition_point(|b| score(b) >= score(beaver));
+    best.insert(index, beaver.clone());
+    best.truncate(keep);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execute::{self, Outcome};
+
+    // enumerate every machine at once
+    fn enumerate(
+        states: usize,
+        symbols: usize,
+        max_steps: usize,
+        max_machines: usize,
+        keep: usize,
+    ) -> Result<Enumeration, String> {
+        let mut enumerator = Enumerator::new(states, symbols, max_steps, max_machines, keep)?;
+        while !enumerator.advance(usize::MAX) {}
+        Ok(enumerator.enumeration)
+    }
+
+    #[test]
+    fn finds_the_known_busy_beavers() {
+        let two = enumerate(2, 2, 50, 100_000, 1).unwrap();
+        assert!(!two.truncated);
+        assert_eq!(two.by_steps[0].steps, 6);
+        assert_eq!(two.by_written[0].written, 4);
+
+        let three = enumerate(3, 2, 50, 1_000_000, 1).unwrap();
+        assert!(!three.truncated);
+        assert_eq!(three.by_steps[0].steps, 21);
+        assert_eq!(three.by_written[0].written, 6);
+    }
+
+    #[test]
+    fn advancing_by_slices_finds_the_same() {
+        let mut enumerator = Enumerator::new(3, 2, 50, 1_000_000, 1).unwrap();
+        let mut slices = 0;
+        while !enumerator.advance(100) {
+            slices += 1;
+        }
+        assert!(slices > 1);
+        assert_eq!(enumerator.enumeration.by_steps[0].steps, 21);
+        assert_eq!(enumerator.enumeration.machines, enumerate(3, 2, 50, 1_000_000, 1).unwrap().machines);
+    }
+
+    #[test]
+    fn generated_code_runs_the_same() {
+        let best = &enumerate(3, 2, 50, 1_000_000, 1).unwrap().by_steps[0];
+        let run = execute::run(&best.machine().unwrap(), "", 1000).unwrap();
+        assert_eq!(run.outcome, Outcome::Accepted);
+        assert_eq!(run.steps, best.steps);
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::{BTreeSet, HashSet};
+
+use turingrs::turing_state::TuringDirection;
+
+use crate::{
+    analysis,
+    examples::EXAMPLES,
+    source::{self, BLANK},
+};
+
+/// Keyword of the lines running another machine as a macro state: `run copy as dup then check;`.
+///
+/// Going to `dup` runs the example `copy` from its initial state on the same ribbons,
+/// then goes on to `check` if it accepts.
+pub const RUN: &str = "run";
+
+/// Above this number of combinations of symbols, a machine is not inlined
+const MAX_COMBINATIONS: usize = 4096;
+
+/// A machine inlined as a macro state
+#[derive(Clone, Debug)]
+pub struct Macro {
+    pub name: String,
+    pub machine: String,
+    pub then: String,
+    /// States of the inlined machine, the first being the macro state itself
+    pub states: Vec<String>,
+}
+
+/// Code with every macro inlined, ready to be compiled
+pub struct Composed {
+    pub code: String,
+    pub macros: Vec<Macro>,
+}
+
+/// Inline the machines run as macro states.
+///
+/// The states of a machine run as `name` are prefixed by `name_`, its initial state being
+/// `name` itself. Its accepting states go on to the next state on whatever is read, without
+/// moving. Its rules keep the ribbons it does not know as they are.
+pub fn expand(code: &str) -> Result<Composed, String> {
+    let examples = |id: &str| EXAMPLES.iter().find(|e| e.id == id).map(|e| e.code);
+    inline(code, &examples, &[])
+}
+
+// inline the machines of a library run by a code, the machines being inlined themselves
+// while `running` lists the machines whose code is being inlined
+fn inline(code: &str, library: &dyn Fn(&str) -> Option<&'static str>, running: &[&str]) -> Result<Composed, String> {
+    let mut calls = vec![];
+    let mut host = vec![];
+    for line in code.lines() {
+        match parse_run(line) {
+            Some(call) => calls.push(call?),
+            None => host.push(line),
+        }
+    }
+
+    if calls.is_empty() {
+        return Ok(Composed { code: code.to_string(), macros: vec![] });
+    }
+
+    let host = host.join("\n");
+    let tm = source::parse(&host)?;
+
+    let mut machines = vec![];
+    for (machine, name, then) in calls.iter() {
+        if running.contains(&machine.as_str()) {
+            return Err(format!("Machine '{}' runs itself", machine));
+        }
+        let code = library(machine).ok_or_else(|| format!("Unknown machine '{}' run as '{}'", machine, name))?;
+        let mut stack = running.to_vec();
+        stack.push(machine);
+        let sub = inline(code, library, &stack)
+            .and_then(|composed| source::parse(&composed.code))
+            .map_err(|e| format!("Machine '{}': {}", machine, e))?;
+        if sub.k > tm.k {
+            return Err(format!(
+                "Machine '{}' uses {} write ribbons, more than the {} of this machine",
+                machine, sub.k, tm.k
+            ));
+        }
+        if let Some(index) = tm.name_index_hashmap.get(name) {
+            if !tm.get_state(*index).transitions.is_empty() {
+                return Err(format!("Macro state '{}' can not have rules of its own", name));
+            }
+        }
+        machines.push((sub, name.clone(), then.clone(), machine.clone()));
+    }
+
+    // a ribbon can hold the symbols of every machine
+    let alphabets: Vec<Vec<char>> = (0..=tm.k as usize)
+        .map(|ribbon| {
+            let mut symbols: BTreeSet<char> = analysis::ribbon_symbols(&tm, ribbon).into_iter().collect();
+            for (sub, ..) in machines.iter().filter(|(sub, ..)| ribbon <= sub.k as usize) {
+                symbols.extend(analysis::ribbon_symbols(sub, ribbon));
+            }
+            symbols.remove(&BLANK);
+            std::iter::once(BLANK).chain(symbols).collect()
+        })
+        .collect();
+
+    let mut names: HashSet<String> = tm.name_index_hashmap.keys().cloned().collect();
+    let mut lines = vec![host];
+    let mut macros = vec![];
+
+    for (sub, name, then, machine) in machines {
+        let rename = |index: u8| {
+            if index == 0 { name.clone() } else { format!("{}_{}", name, source::state_name(&sub, index)) }
+        };
+
+        let states: Vec<String> = (0..sub.states.len() as u8).map(&rename).collect();
+        for state in states.iter().skip(1) {
+            if !names.insert(state.clone()) {
+                return Err(format!("State '{}' of the macro '{}' already exists", state, name));
+            }
+        }
+
+        lines.push(String::new());
+        lines.push(format!("// {} {} as {} then {}", RUN, machine, name, then));
+
+        let extra = combinations(&alphabets[sub.k as usize + 1..])?;
+        for index in 0..sub.states.len() as u8 {
+            let state = sub.get_state(index);
+
+            for t in state.transitions.iter() {
+                for symbols in extra.iter() {
+                    let mut read = t.chars_read.clone();
+                    read.extend(symbols.iter());
+                    let mut write = t.chars_write.clone();
+                    write.extend(symbols.iter().map(|c| (*c, TuringDirection::None)));
+
+                    let body = source::format_rule(&read, &t.move_read, &write);
+                    lines.push(source::rule_line(&rename(index), &body, &rename(t.index_to_state)));
+                }
+            }
+
+            // leave the macro from its accepting states, when none of its rules apply
+            if state.is_final {
+                for read in combinations(&alphabets)? {
+                    if state.transitions.iter().any(|t| read.starts_with(&t.chars_read)) {
+                        continue;
+                    }
+                    let write: Vec<(char, TuringDirection)> =
+                        read[1..].iter().map(|c| (*c, TuringDirection::None)).collect();
+                    let body = source::format_rule(&read, &TuringDirection::None, &write);
+                    lines.push(source::rule_line(&rename(index), &body, &then));
+                }
+            }
+        }
+
+        macros.push(Macro { name, machine, then, states });
+    }
+
+    Ok(Composed { code: lines.join("\n"), macros })
+}
+
+// parse a line running a machine as a macro state: machine, macro state and next state
+fn parse_run(line: &str) -> Option<Result<(String, String, String), String>> {
+    let rest = line.trim().strip_prefix(RUN)?;
+    // a rule of a state named like the keyword
+    if !rest.starts_with(char::is_whitespace) || rest.contains('{') {
+        return None;
+    }
+
+    let words: Vec<&str> = rest.trim().trim_end_matches(';').split_whitespace().collect();
+    Some(match words.as_slice() {
+        [machine, "as", name, "then", then] => Ok((machine.to_string(), name.to_string(), then.to_string())),
+        _ => Err(format!(
+            "Invalid line '{}', expected '{} <machine> as <state> then <state>;'",
+            line.trim(),
+            RUN
+        )),
+    })
+}
+
+// every combination of a symbol of each alphabet
+fn combinations(alphabets: &[Vec<char>]) -> Result<Vec<Vec<char>>, String> {
+    let count = alphabets.iter().map(Vec::len).product::<usize>();
+    if count > MAX_COMBINATIONS {
+        return Err(format!(
+            "The ribbons can hold {} combinations of symbols, more than the {} supported to inline a machine",
+            count, MAX_COMBINATIONS
+        ));
+    }
+
+    Ok(alphabets.iter().fold(vec![vec![]], |combinations, alphabet| {
+        combinations
+            .iter()
+            .flat_map(|c| {
+                alphabet.iter().map(move |s| {
+                    let mut c = c.clone();
+                    c.push(*s);
+                    c
+                })
+            })
+            .collect()
+    }))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execute::{self, Outcome};
+
+    // writes x on the first write ribbon for every a
+    const MARK: &str = "accepting: end;\n\n\
+        first {a, _ -> R, x, R} first;\n\
+        first {_, _ -> N, _, N} end;";
+
+    // runs itself from its second state
+    const LOOPING: &str = "accepting: end;\n\n\
+        start {_ -> N} again;\n\
+        run looping as again then end;";
+
+    fn library(id: &str) -> Option<&'static str> {
+        match id {
+            "mark" => Some(MARK),
+            "looping" => Some(LOOPING),
+            _ => None,
+        }
+    }
+
+    fn compose(code: &str) -> Result<Composed, String> {
+        inline(code, &library, &[])
+    }
+
+    #[test]
+    fn code_without_macros_is_kept() {
+        let code = "accepting: done;\n\nstart {a -> R} done;";
+        let composed = compose(code).unwrap();
+        assert_eq!(composed.code, code);
+        assert!(composed.macros.is_empty());
+    }
+
+    #[test]
+    fn states_are_prefixed_by_the_macro_state() {
+        let composed = compose("accepting: done;\n\nstart {a, _ -> N, _, N} go;\nrun mark as go then done;").unwrap();
+        let tm = source::parse(&composed.code).unwrap();
+
+        assert_eq!(composed.macros.len(), 1);
+        let m = &composed.macros[0];
+        assert_eq!((m.name.as_str(), m.machine.as_str(), m.then.as_str()), ("go", "mark", "done"));
+        assert_eq!(m.states, ["go", "go_end"]);
+        for state in m.states.iter() {
+            assert!(tm.name_index_hashmap.contains_key(state), "{}", state);
+        }
+    }
+
+    #[test]
+    fn accepting_states_go_on_to_the_next_state() {
+        let composed = compose("accepting: done;\n\nstart {a, _ -> N, _, N} go;\nrun mark as go then done;").unwrap();
+        let tm = source::parse(&composed.code).unwrap();
+
+        let run = execute::run(&tm, "aa", 100).unwrap();
+        assert_eq!(run.outcome, Outcome::Accepted);
+        assert_eq!(run.output(), "xx");
+        // the macro accepts but the next state refuses
+        let composed = compose("accepting: done;\n\nstart {a, _ -> N, _, N} go;\nrun mark as go then stop;").unwrap();
+        let tm = source::parse(&composed.code).unwrap();
+        assert_eq!(execute::run(&tm, "aa", 100).unwrap().outcome, Outcome::Refused);
+    }
+
+    #[test]
+    fn ribbons_the_macro_does_not_know_are_kept() {
+        let code = "accepting: done;\n\nstart {a, _, _ -> N, _, N, y, N} go;\nrun mark as go then done;";
+        let tm = source::parse(&compose(code).unwrap().code).unwrap();
+
+        let run = execute::run(&tm, "a", 100).unwrap();
+        assert_eq!(run.outcome, Outcome::Accepted);
+        assert_eq!(run.output(), "x | y");
+
+        let error = compose("accepting: done;\n\nstart {a -> N} go;\nrun mark as go then done;").unwrap_err();
+        assert!(error.contains("write ribbons"), "{}", error);
+    }
+
+    #[test]
+    fn names_can_not_collide() {
+        let code = "accepting: done;\n\nstart {a, _ -> N, _, N} go;\ngo_end {a, _ -> N, _, N} done;\nrun mark as go then done;";
+        let error = compose(code).unwrap_err();
+        assert!(error.contains("already exists"), "{}", error);
+
+        let code = "accepting: done;\n\nstart {a, _ -> N, _, N} go;\ngo {a, _ -> N, _, N} done;\nrun mark as go then done;";
+        let error = compose(code).unwrap_err();
+        assert!(error.contains("rules of its own"), "{}", error);
+    }
+
+    #[test]
+    fn machines_can_not_run_themselves() {
+        let error = compose("accepting: done;\n\nstart {a -> N} go;\nrun looping as go then done;").unwrap_err();
+        assert!(error.contains("runs itself"), "{}", error);
+    }
+
+    #[test]
+    fn run_lines_name_known_machines() {
+        let error = compose("accepting: done;\n\nstart {a -> N} go;\nrun nothing as go then done;").unwrap_err();
+        assert!(error.contains("Unknown machine 'nothing'"), "{}", error);
+
+        let error = compose("accepting: done;\n\nstart {a -> N} go;\nrun mark go done;").unwrap_err();
+        assert!(error.contains("Invalid line"), "{}", error);
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::BTreeSet;
+
+use turingrs::turing_machine::TuringMachine;
+
+use crate::{
+    analysis,
+    execute::{self, Outcome, Run},
+    source::BLANK,
+};
+
+/// An input on which two machines disagree
+#[derive(Clone, Debug)]
+pub struct Counterexample {
+    pub input: String,
+    pub first: Outcome,
+    pub second: Outcome,
+    /// Output tapes of both machines, when they are compared
+    pub outputs: Option<(String, String)>,
+}
+
+/// Result of a bounded comparison of two machines
+#[derive(Clone, Debug)]
+pub struct Report {
+    /// Length of the longest words compared
+    pub max_length: usize,
+    /// Number of inputs run on both machines
+    pub checked: usize,
+    /// Inputs on which a machine ran out of budget, where nothing can be said
+    pub inconclusive: Vec<String>,
+    pub counterexample: Option<Counterexample>,
+    /// The comparison was stopped before the end
+    pub stopped: bool,
+}
+
+/// Symbols the read ribbon of either machine knows, without the blank
+pub fn alphabet(first: &TuringMachine, second: &TuringMachine) -> String {
+    let mut symbols: BTreeSet<char> = analysis::ribbon_symbols(first, 0).into_iter().collect();
+    symbols.extend(analysis::ribbon_symbols(second, 0));
+    symbols.remove(&BLANK);
+    symbols.into_iter().collect()
+}
+
+/// A comparison going on, advanced a slice at a time to keep the interface responsive
+pub struct Checker {
+    first: TuringMachine,
+    second: TuringMachine,
+    words: Box<dyn Iterator<Item = String>>,
+    budget: usize,
+    outputs: bool,
+    done: bool,
+    pub report: Report,
+}
+
+impl Checker {
+    /// Compare the verdicts of two machines, and their output tapes if asked, on every word over
+    /// the alphabet up to a length, shortest first. Each run has a budget of steps.
+    pub fn new(
+        first: &TuringMachine,
+        second: &TuringMachine,
+        alphabet: &[char],
+        max_length: usize,
+        budget: usize,
+        outputs: bool,
+    ) -> Self {
+        Checker {
+            first: first.clone(),
+            second: second.clone(),
+            words: Box::new(execute::words(alphabet, max_length)),
+            budget,
+            outputs,
+            done: false,
+            report: Report { max_length, checked: 0, inconclusive: vec![], counterexample: None, stopped: false },
+        }
+    }
+
+    /// Whether every word was compared, a counterexample found or the comparison stopped
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    /// Stop the comparison, the words compared so far being kept
+    pub fn stop(&mut self) {
+        self.report.stopped |= !self.done;
+        self.done = true;
+    }
+
+    /// Compare words until about `work` steps were run, returning whether the comparison is over
+    pub fn advance(&mut self, work: usize) -> Result<bool, String> {
+        let mut spent = 0;
+        while !self.done && spent < work {
+            let Some(input) = self.words.next() else {
+                self.done = true;
+                break;
+            };
+            let a = execute::run(&self.first, &input, self.budget)
+                .map_err(|e| format!("Current machine on '{}': {}", input, e))?;
+            let b = execute::run(&self.second, &input, self.budget)
+                .map_err(|e| format!("Second machine on '{}': {}", input, e))?;
+            self.report.checked += 1;
+            spent += (a.steps + b.steps).max(1);
+
+            if a.outcome == Outcome::Timeout || b.outcome == Outcome::Timeout {
+                self.report.inconclusive.push(input);
+                continue;
+            }
+
+            if let Some(counterexample) = compare(input, &a, &b, self.outputs) {
+                self.report.counterexample = Some(counterexample);
+                self.done = true;
+            }
+        }
+        Ok(self.done)
+    }
+}
+
+// the counterexample two halting runs make, if they differ
+fn compare(input: String, a: &Run, b: &Run, outputs: bool) -> Option<Counterexample> {
+    let tapes = outputs.then(|| (a.output(), b.output()));
+    let same_tapes = tapes.as_ref().is_none_or(|(a, b)| a == b);
+
+    if a.outcome == b.outcome && same_tapes {
+        return None;
+    }
+    Some(Counterexample { input, first: a.outcome, second: b.outcome, outputs: tapes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source;
+
+    // accepts the words of a
+    const ONLY_A: &str = "accepting: done;\n\n\
+        start {a -> R} start;\n\
+        start {_ -> L} done;";
+
+    // accepts every word
+    const EVERY_WORD: &str = "accepting: done;\n\n\
+        start {a -> R} start;\n\
+        start {b -> R} start;\n\
+        start {_ -> L} done;";
+
+    // never halts on the words starting with a
+    const LOOP: &str = "accepting: done;\n\n\
+        start {a -> N} start;\n\
+        start {_ -> L} done;";
+
+    // copy the input, writing b for a in the second one
+    const COPY: &str = "accepting: done;\n\n\
+        start {a, _ -> R, a, R} start;\n\
+        start {_, _ -> N, _, N} done;";
+    const COPY_B: &str = "accepting: done;\n\n\
+        start {a, _ -> R, b, R} start;\n\
+        start {_, _ -> N, _, N} done;";
+
+    fn comparison(first: &str, second: &str, alphabet: &[char], max_length: usize, outputs: bool) -> Checker {
+        let (first, second) = (source::parse(first).unwrap(), source::parse(second).unwrap());
+        Checker::new(&first, &second, alphabet, max_length, 1000, outputs)
+    }
+
+    fn check(first: &str, second: &str, alphabet: &[char], max_length: usize, outputs: bool) -> Report {
+        let mut checker = comparison(first, second, alphabet, max_length, outputs);
+        while !checker.advance(usize::MAX).unwrap() {}
+        checker.report
+    }
+
+    #[test]
+    fn the_shortest_counterexample_is_found() {
+        let report = check(ONLY_A, EVERY_WORD, &['a', 'b'], 3, false);
+        let counterexample = report.counterexample.unwrap();
+        assert_eq!(counterexample.input, "b");
+        assert_eq!((counterexample.first, counterexample.second), (Outcome::Refused, Outcome::Accepted));
+        assert_eq!(report.checked, 3);
+        assert!(!report.stopped);
+    }
+
+    #[test]
+    fn runs_out_of_budget_are_inconclusive() {
+        let report = check(ONLY_A, LOOP, &['a', 'b'], 2, false);
+        assert!(report.counterexample.is_none());
+        assert_eq!(report.inconclusive, ["a", "aa", "ab"]);
+        assert_eq!(report.checked, 7);
+        assert_eq!(report.max_length, 2);
+    }
+
+    #[test]
+    fn outputs_are_compared_when_asked() {
+        assert!(check(COPY, COPY_B, &['a'], 2, false).counterexample.is_none());
+
+        let counterexample = check(COPY, COPY_B, &['a'], 2, true).counterexample.unwrap();
+        assert_eq!(counterexample.input, "a");
+        assert_eq!(counterexample.outputs, Some((String::from("a"), String::from("b"))));
+    }
+
+    #[test]
+    fn advancing_by_slices_compares_a_word_at_a_time() {
+        let mut checker = comparison(ONLY_A, ONLY_A, &['a'], 3, false);
+        for checked in 1..=4 {
+            assert!(!checker.advance(1).unwrap());
+            assert_eq!(checker.report.checked, checked);
+        }
+        assert!(checker.advance(1).unwrap());
+        assert!(checker.report.counterexample.is_none() && !checker.report.stopped);
+
+        let mut checker = comparison(ONLY_A, ONLY_A, &['a'], 3, false);
+        checker.advance(1).unwrap();
+        checker.stop();
+        assert!(checker.done() && checker.report.stopped);
+    }
+}
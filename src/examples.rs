@@ -0,0 +1,77 @@
+/// A machine bundled with the application
+pub struct Example {
+    /// Name of the file of the example, used to run it from another machine
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub code: &'static str,
+    pub input: &'static str,
+}
+
+pub static EXAMPLES: [Example; 7] = [
+    Example {
+        id: "binary_increment",
+        name: "Binary increment",
+        description: "Copies a binary number on the write ribbon and adds one to it.",
+        code: include_str!("../assets/examples/binary_increment.tm"),
+        input: "1011",
+    },
+    Example {
+        id: "palindrome",
+        name: "Palindromes",
+        description: "Accepts the words over {a, b} that read the same in both directions.",
+        code: include_str!("../assets/examples/palindrome.tm"),
+        input: "abba",
+    },
+    Example {
+        id: "anbncn",
+        name: "aⁿbⁿcⁿ",
+        description: "Accepts aⁿbⁿcⁿ by counting the a and the b on two write ribbons.",
+        code: include_str!("../assets/examples/anbncn.tm"),
+        input: "aabbcc",
+    },
+    Example {
+        id: "unary_addition",
+        name: "Unary addition",
+        description: "Writes the sum of two unary numbers separated by a +.",
+        code: include_str!("../assets/examples/unary_addition.tm"),
+        input: "111+11",
+    },
+    Example {
+        id: "copy",
+        name: "Multi-tape copy",
+        description: "Copies the input on the first write ribbon, then reversed on the second.",
+        code: include_str!("../assets/examples/copy.tm"),
+        input: "0110",
+    },
+    Example {
+        id: "busy_beaver",
+        name: "Busy beaver",
+        description: "The 2-state busy beaver: writes four 1 in six steps on an empty tape.",
+        code: include_str!("../assets/examples/busy_beaver.tm"),
+        input: "",
+    },
+    Example {
+        id: "universal",
+        name: "Universal machine",
+        description: "Runs the machine encoded before 111 on the word after it, here the words with an even number of 1.",
+        code: include_str!("../assets/examples/universal.tm"),
+        input: "01010101001101001000100100110001010001010011000100101001001101000100100010001110110",
+    },
+];
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compose, execute, source};
+
+    #[test]
+    fn every_example_compiles_and_runs_on_its_input() {
+        for example in EXAMPLES.iter() {
+            let composed = compose::expand(example.code).unwrap_or_else(|e| panic!("{}: {}", example.id, e));
+            let tm = source::parse(&composed.code).unwrap_or_else(|e| panic!("{}: {}", example.id, e));
+            assert!(execute::run(&tm, example.input, 100_000).is_ok(), "{}", example.id);
+        }
+    }
+}
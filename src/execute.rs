@@ -0,0 +1,82 @@
+use itertools::Itertools;
+use turingrs::turing_machine::{TuringExecutionStep, TuringExecutor, TuringMachine, TuringMachineExecutor};
+
+use crate::source::BLANK;
+
+/// How a run of a machine on an input ended
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    Accepted,
+    Refused,
+    /// Still running after the maximum number of steps
+    Timeout,
+}
+
+impl Outcome {
+    /// How the outcome is shown to the user
+    pub fn label(&self) -> &'static str {
+        match self {
+            Outcome::Accepted => "accepted",
+            Outcome::Refused => "refused",
+            Outcome::Timeout => "out of budget",
+        }
+    }
+}
+
+/// A run of a machine on an input, without displaying it
+pub struct Run {
+    pub outcome: Outcome,
+    pub steps: usize,
+    /// Last configuration reached
+    pub last: TuringExecutionStep,
+}
+
+impl Run {
+    /// Number of cells of all the ribbons used by the run, the ribbons only growing
+    pub fn space(&self) -> usize {
+        let write: usize = self.last.write_ribbons.iter().map(|r| r.chars_vec.len()).sum();
+        self.last.read_ribbon.chars_vec.len() + write
+    }
+
+    /// Content of the write ribbons at the end of the run, without the blanks around
+    pub fn output(&self) -> String {
+        self.last
+            .write_ribbons
+            .iter()
+            .map(|r| r.chars_vec.iter().collect::<String>().trim_matches(BLANK).to_string())
+            .join(" | ")
+    }
+}
+
+/// Run a machine on an input until it halts or after the maximum number of steps
+pub fn run(tm: &TuringMachine, input: &str, max_steps: usize) -> Result<Run, String> {
+    let (mut executor, mut last) =
+        TuringMachineExecutor::new(tm.clone(), input.to_string()).map_err(|e| format!("{:?}", e))?;
+
+    // one more call to tell a machine halting after exactly max_steps from a timeout
+    for steps in 0..=max_steps {
+        match executor.as_iter().next() {
+            Some(step) if steps < max_steps => last = step,
+            Some(_) => break,
+            None => {
+                let accepted = executor.turing_machine.get_state(executor.get_state_pointer()).is_final;
+                let outcome = if accepted { Outcome::Accepted } else { Outcome::Refused };
+                return Ok(Run { outcome, steps, last });
+            }
+        }
+    }
+    Ok(Run { outcome: Outcome::Timeout, steps: max_steps, last })
+}
+
+/// Every word over an alphabet up to a length, shortest first
+pub fn words(alphabet: &[char], max_length: usize) -> impl Iterator<Item = String> + use<> {
+    let alphabet = alphabet.to_vec();
+    (0..=max_length).flat_map(move |length| {
+        (0..length)
+            .map(|_| alphabet.clone().into_iter())
+            .multi_cartesian_product()
+            .map(|word| word.into_iter().collect::<String>())
+            // the product of no alphabet is empty instead of the empty word
+            .chain((length == 0).then(String::new))
+    })
+}
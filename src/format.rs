@@ -0,0 +1,146 @@
+use std::collections::{BTreeSet, HashMap};
+
+use egui::Pos2;
+use turingrs::turing_state::TuringDirection;
+
+use crate::{
+    TuringApp,
+    source::{self, BLANK},
+};
+
+pub mod dot;
+pub mod jflap;
+pub mod tikz;
+pub mod yaml;
+
+/// Supported file formats for import and export
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    Dot,
+    Tikz,
+    Jflap,
+    Yaml,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [Format::Dot, Format::Tikz, Format::Jflap, Format::Yaml];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Dot => "Graphviz DOT",
+            Format::Tikz => "LaTeX TikZ",
+            Format::Jflap => "JFLAP",
+            Format::Yaml => "turingmachine.io YAML",
+        }
+    }
+
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Format::Dot => &["dot", "gv"],
+            Format::Tikz => &["tex"],
+            Format::Jflap => &["jff"],
+            Format::Yaml => &["yaml", "yml"],
+        }
+    }
+
+    // whether a machine can be read back from this format
+    pub fn can_import(&self) -> bool {
+        !matches!(self, Format::Tikz)
+    }
+}
+
+/// Result of an import: the code of the machine, ready to be compiled, and the layout of the graph
+#[derive(Default, Debug)]
+pub struct ImportedMachine {
+    pub code: String,
+    pub input: Option<String>,
+    pub positions: HashMap<String, Pos2>,
+    pub warnings: Vec<String>,
+}
+
+// export the current machine and graph in the given format
+pub fn export(format: Format, app: &TuringApp) -> Result<String, String> {
+    match format {
+        Format::Dot => Ok(dot::export(&app.turing.turing_machine, &app.states_hash)),
+        Format::Tikz => Ok(tikz::export(&app.turing.turing_machine, &app.states_hash)),
+        Format::Jflap => Ok(jflap::export(&app.turing.turing_machine, &app.states_hash)),
+        Format::Yaml => yaml::export(&app.turing.turing_machine, &app.input),
+    }
+}
+
+// import a machine from the content of a file in the given format
+pub fn import(format: Format, text: &str) -> Result<ImportedMachine, String> {
+    match format {
+        Format::Dot => dot::import(text),
+        Format::Tikz => Err(String::from("TikZ pictures can only be exported")),
+        Format::Jflap => jflap::import(text),
+        Format::Yaml => yaml::import(text),
+    }
+}
+
+/// States added in front of an imported read-write tape machine
+const COPY_STATE: &str = "input_copy";
+const REWIND_STATE: &str = "input_rewind";
+
+/// Rules copying the input from the read ribbon to the first of `tapes` write ribbons, then
+/// moving back to its first symbol before going to `initial`.
+///
+/// Formats where the input tape is read-write rely on it since the read ribbon can not be
+/// written. Once done, the read ribbon stays on the blank after the input.
+fn input_prologue(alphabet: &BTreeSet<char>, tapes: usize, initial: &str) -> Vec<String> {
+    let mut lines = vec![];
+    let rule = |read: Vec<char>, move_read: TuringDirection, first: (char, TuringDirection)| {
+        let mut all_read = read;
+        all_read.extend((1..tapes).map(|_| BLANK));
+        let mut write = vec![first];
+        write.extend((1..tapes).map(|_| (BLANK, TuringDirection::None)));
+        source::format_rule(&all_read, &move_read, &write)
+    };
+
+    for c in alphabet.iter() {
+        lines.push(source::rule_line(
+            COPY_STATE,
+            &rule(vec![*c, BLANK], TuringDirection::Right, (*c, TuringDirection::Right)),
+            COPY_STATE,
+        ));
+        lines.push(source::rule_line(
+            REWIND_STATE,
+            &rule(vec![BLANK, *c], TuringDirection::None, (*c, TuringDirection::Left)),
+            REWIND_STATE,
+        ));
+    }
+    lines.push(source::rule_line(
+        COPY_STATE,
+        &rule(vec![BLANK, BLANK], TuringDirection::None, (BLANK, TuringDirection::Left)),
+        REWIND_STATE,
+    ));
+    lines.push(source::rule_line(
+        REWIND_STATE,
+        &rule(vec![BLANK, BLANK], TuringDirection::None, (BLANK, TuringDirection::Right)),
+        initial,
+    ));
+
+    lines
+}
+
+// a machine compiled from its code with the states of its graph, for the tests of the formats
+#[cfg(test)]
+fn graph(code: &str) -> (turingrs::turing_machine::TuringMachine, HashMap<u8, crate::ui::turing::State>) {
+    let tm = source::parse(code).unwrap();
+    let states = tm
+        .name_index_hashmap
+        .iter()
+        .map(|(name, index)| {
+            let position = Pos2::new(*index as f32 * 10.0, 5.0);
+            (*index, crate::ui::turing::State { name: name.clone(), position, ..Default::default() })
+        })
+        .collect();
+    (tm, states)
+}
+
+// state names of imported machines, that can not contain spaces nor separators in the code
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
@@ -0,0 +1,495 @@
+use std::collections::HashMap;
+
+use egui::Pos2;
+use turingrs::turing_machine::TuringMachine;
+
+use crate::{
+    source::{self, ACCEPTING},
+    ui::turing::State,
+};
+
+use super::{ImportedMachine, sanitize};
+
+/// Name of the invisible node pointing to the initial state
+const START_NODE: &str = "__start";
+
+/// Export the machine and its layout to a Graphviz DOT graph.
+///
+/// Every rule is an edge labelled with the rule body, and node positions are written as
+/// pinned `pos` attributes (y axis flipped since Graphviz points up).
+pub fn export(tm: &TuringMachine, states: &HashMap<u8, State>) -> String {
+    let mut lines = vec![
+        String::from("digraph turing {"),
+        String::from("    rankdir=LR;"),
+        String::from("    node [shape=circle];"),
+        format!("    {} [shape=point];", START_NODE),
+    ];
+
+    let mut indexes: Vec<u8> = states.keys().copied().collect();
+    indexes.sort();
+
+    for index in indexes.iter() {
+        let state = &states[index];
+        let shape = if tm.get_state(*index).is_final {
+            "doublecircle"
+        } else {
+            "circle"
+        };
+        lines.push(format!(
+            "    {} [shape={}, pos=\"{},{}!\"];",
+            quote(&state.name),
+            shape,
+            state.position.x,
+            -state.position.y
+        ));
+    }
+
+    if let Some(initial) = states.get(&0) {
+        lines.push(format!("    {} -> {};", START_NODE, quote(&initial.name)));
+    }
+
+    for index in indexes.iter() {
+        for t in tm.get_state(*index).transitions.iter() {
+            lines.push(format!(
+                "    {} -> {} [label={}];",
+                quote(&states[index].name),
+                quote(&source::state_name(tm, t.index_to_state)),
+                quote(&source::rule_body(t))
+            ));
+        }
+    }
+
+    lines.push(String::from("}"));
+    lines.join("\n")
+}
+
+/// Import a Graphviz DOT graph whose edge labels are rule bodies.
+///
+/// The initial state is the target of an edge coming from a `point` node, or the first node
+/// declared. `doublecircle` nodes are accepting. Several rules can share an edge when
+/// separated by line breaks.
+pub fn import(text: &str) -> Result<ImportedMachine, String> {
+    let graph = parse(&tokenize(text)?)?;
+    let mut imported = ImportedMachine::default();
+
+    // name of every node, the label taking precedence over the id
+    let label_of = |id: &str| -> String {
+        graph
+            .nodes
+            .iter()
+            .find(|(n, _)| n == id)
+            .and_then(|(_, attrs)| attrs.get("label"))
+            .filter(|l| !l.is_empty() && l.as_str() != "\\N")
+            .cloned()
+            .unwrap_or_else(|| id.to_string())
+    };
+    let name_of = |id: &str| sanitize(&label_of(id));
+
+    let is_marker = |id: &str| {
+        id.starts_with(START_NODE)
+            || graph.nodes.iter().any(|(n, attrs)| {
+                n == id && attrs.get("shape").is_some_and(|s| s == "point")
+            })
+    };
+
+    let initial = graph
+        .edges
+        .iter()
+        .find(|(from, to, _)| is_marker(from) && !is_marker(to))
+        .map(|(_, to, _)| to.clone())
+        .or_else(|| {
+            graph
+                .nodes
+                .iter()
+                .map(|(n, _)| n.clone())
+                .find(|n| !is_marker(n))
+        })
+        .ok_or("The graph has no node")?;
+
+    // initial state first, then in declaration order
+    let mut order: Vec<String> = vec![initial.clone()];
+    for (id, _) in graph.nodes.iter() {
+        if !is_marker(id) && !order.contains(id) {
+            order.push(id.clone());
+        }
+    }
+    for (from, to, _) in graph.edges.iter() {
+        for id in [from, to] {
+            if !is_marker(id) && !order.contains(id) {
+                order.push(id.clone());
+            }
+        }
+    }
+
+    let mut accepting = vec![];
+    for (id, attrs) in graph.nodes.iter() {
+        if is_marker(id) {
+            continue;
+        }
+        if name_of(id) != label_of(id) {
+            imported.warnings.push(format!(
+                "State '{}' was renamed '{}', state names can not contain spaces nor separators",
+                label_of(id),
+                name_of(id)
+            ));
+        }
+        if attrs.get("shape").is_some_and(|s| s == "doublecircle")
+            || attrs
+                .get("peripheries")
+                .and_then(|p| p.parse::<u32>().ok())
+                .is_some_and(|p| p >= 2)
+        {
+            accepting.push(name_of(id));
+        }
+        if let Some(pos) = attrs.get("pos").and_then(|p| parse_pos(p)) {
+            imported.positions.insert(name_of(id), pos);
+        }
+    }
+
+    let mut lines = vec![];
+    if !accepting.is_empty() {
+        lines.push(format!("{}: {};", ACCEPTING, accepting.join(", ")));
+        lines.push(String::new());
+    }
+
+    for id in order.iter() {
+        for (from, to, attrs) in graph.edges.iter().filter(|(from, _, _)| from == id) {
+            let Some(label) = attrs.get("label") else {
+                imported.warnings.push(format!(
+                    "Edge {} -> {} has no label and was ignored",
+                    name_of(from),
+                    name_of(to)
+                ));
+                continue;
+            };
+            for body in label
+                .split(['\n'])
+                .flat_map(|l| l.split("\\n"))
+                .flat_map(|l| l.split("\\l"))
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+            {
+                lines.push(source::rule_line(&name_of(from), body, &name_of(to)));
+            }
+        }
+    }
+
+    imported.code = lines.join("\n");
+
+    // make sure the rules follow the syntax before loading anything
+    source::parse(&imported.code)?;
+
+    Ok(imported)
+}
+
+// parse a graphviz position "x,y" or "x,y!"
+fn parse_pos(text: &str) -> Option<Pos2> {
+    let mut coords = text.trim_end_matches('!').split(',');
+    let x = coords.next()?.trim().parse::<f32>().ok()?;
+    let y = coords.next()?.trim().parse::<f32>().ok()?;
+    Some(Pos2::new(x, -y))
+}
+
+// quote and escape an identifier
+fn quote(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+#[derive(PartialEq, Debug, Clone)]
+enum Token {
+    Id(String),
+    Edge,
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    Equal,
+    Comma,
+    Semi,
+}
+
+// split a dot file into tokens, skipping comments
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '#' => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if last == '*' => break,
+                        Some(c) => last = c,
+                        None => return Err(String::from("Unterminated comment")),
+                    }
+                }
+            }
+            '-' if chars.peek() == Some(&'>') || chars.peek() == Some(&'-') => {
+                chars.next();
+                tokens.push(Token::Edge);
+            }
+            '[' => tokens.push(Token::LBracket),
+            ']' => tokens.push(Token::RBracket),
+            '{' => tokens.push(Token::LBrace),
+            '}' => tokens.push(Token::RBrace),
+            '=' => tokens.push(Token::Equal),
+            ',' => tokens.push(Token::Comma),
+            ';' => tokens.push(Token::Semi),
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('"') => s.push('"'),
+                            Some('\n') => {}
+                            Some(c) => {
+                                s.push('\\');
+                                s.push(c);
+                            }
+                            None => return Err(String::from("Unterminated string")),
+                        },
+                        Some(c) => s.push(c),
+                        None => return Err(String::from("Unterminated string")),
+                    }
+                }
+                tokens.push(Token::Id(s.replace("\\\\", "\\")));
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut s = String::from(c);
+                while let Some(c) =
+                    chars.next_if(|c| c.is_alphanumeric() || *c == '_' || *c == '.')
+                {
+                    s.push(c);
+                }
+                tokens.push(Token::Id(s));
+            }
+            '<' => {
+                // html label, kept as is
+                let mut depth = 1;
+                let mut s = String::new();
+                while depth > 0 {
+                    match chars.next() {
+                        Some('<') => depth += 1,
+                        Some('>') => depth -= 1,
+                        Some(c) => s.push(c),
+                        None => return Err(String::from("Unterminated html label")),
+                    }
+                }
+                tokens.push(Token::Id(s));
+            }
+            c => return Err(format!("Unexpected character '{}'", c)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+type Attributes = HashMap<String, String>;
+
+#[derive(Default)]
+struct Graph {
+    nodes: Vec<(String, Attributes)>,
+    edges: Vec<(String, String, Attributes)>,
+}
+
+// parse the statements of a graph, subgraphs are flattened
+fn parse(tokens: &[Token]) -> Result<Graph, String> {
+    let mut graph = Graph::default();
+    let mut node_defaults: Attributes = HashMap::new();
+    let mut edge_defaults: Attributes = HashMap::new();
+    let mut i = 0;
+
+    let keyword = |t: Option<&Token>, k: &str| matches!(t, Some(Token::Id(s)) if s.eq_ignore_ascii_case(k));
+
+    // header
+    if keyword(tokens.get(i), "strict") {
+        i += 1;
+    }
+    if !keyword(tokens.get(i), "digraph") && !keyword(tokens.get(i), "graph") {
+        return Err(String::from("Expected a graph or digraph"));
+    }
+    i += 1;
+    if let Some(Token::Id(_)) = tokens.get(i) {
+        i += 1;
+    }
+    if tokens.get(i) != Some(&Token::LBrace) {
+        return Err(String::from("Expected '{' after the graph name"));
+    }
+    i += 1;
+
+    let mut depth = 1;
+    while depth > 0 {
+        match tokens.get(i) {
+            None => return Err(String::from("Missing closing '}'")),
+            Some(Token::RBrace) => {
+                depth -= 1;
+                i += 1;
+            }
+            Some(Token::LBrace) => {
+                depth += 1;
+                i += 1;
+            }
+            Some(Token::Semi) | Some(Token::Comma) => i += 1,
+            Some(Token::Id(s)) if s.eq_ignore_ascii_case("subgraph") => {
+                i += 1;
+                if let Some(Token::Id(_)) = tokens.get(i) {
+                    i += 1;
+                }
+            }
+            Some(Token::Id(s))
+                if ["node", "edge", "graph"].iter().any(|k| s.eq_ignore_ascii_case(k))
+                    && tokens.get(i + 1) == Some(&Token::LBracket) =>
+            {
+                let kind = s.to_lowercase();
+                let (attrs, next) = parse_attributes(tokens, i + 1)?;
+                match kind.as_str() {
+                    "node" => node_defaults.extend(attrs),
+                    "edge" => edge_defaults.extend(attrs),
+                    // the attributes of the graph itself only change its layout
+                    _ => {}
+                }
+                i = next;
+            }
+            Some(Token::Id(_)) if tokens.get(i + 1) == Some(&Token::Equal) => {
+                // graph attribute
+                i += 3;
+            }
+            Some(Token::Id(first)) => {
+                let mut chain = vec![first.clone()];
+                i += 1;
+                while tokens.get(i) == Some(&Token::Edge) {
+                    match tokens.get(i + 1) {
+                        Some(Token::Id(id)) => chain.push(id.clone()),
+                        _ => return Err(String::from("Expected a node after an edge")),
+                    }
+                    i += 2;
+                }
+
+                let mut attrs = HashMap::new();
+                if tokens.get(i) == Some(&Token::LBracket) {
+                    let (a, next) = parse_attributes(tokens, i)?;
+                    attrs = a;
+                    i = next;
+                }
+
+                if chain.len() == 1 {
+                    match graph.nodes.iter_mut().find(|(n, _)| *n == chain[0]) {
+                        Some((_, existing)) => existing.extend(attrs),
+                        None => {
+                            let mut all = node_defaults.clone();
+                            all.extend(attrs);
+                            graph.nodes.push((chain[0].clone(), all));
+                        }
+                    }
+                } else {
+                    for id in chain.iter() {
+                        if !graph.nodes.iter().any(|(n, _)| n == id) {
+                            graph.nodes.push((id.clone(), node_defaults.clone()));
+                        }
+                    }
+                    for pair in chain.windows(2) {
+                        let mut all = edge_defaults.clone();
+                        all.extend(attrs.clone());
+                        graph.edges.push((pair[0].clone(), pair[1].clone(), all));
+                    }
+                }
+            }
+            Some(t) => return Err(format!("Unexpected token {:?}", t)),
+        }
+    }
+
+    Ok(graph)
+}
+
+// parse one or more attribute lists starting at a '[' token
+fn parse_attributes(tokens: &[Token], mut i: usize) -> Result<(Attributes, usize), String> {
+    let mut attrs = HashMap::new();
+    while tokens.get(i) == Some(&Token::LBracket) {
+        i += 1;
+        loop {
+            match (tokens.get(i), tokens.get(i + 1), tokens.get(i + 2)) {
+                (Some(Token::RBracket), _, _) => {
+                    i += 1;
+                    break;
+                }
+                (Some(Token::Comma), _, _) | (Some(Token::Semi), _, _) => i += 1,
+                (Some(Token::Id(k)), Some(Token::Equal), Some(Token::Id(v))) => {
+                    attrs.insert(k.to_lowercase(), v.clone());
+                    i += 3;
+                }
+                (Some(Token::Id(_)), _, _) => i += 1,
+                _ => return Err(String::from("Malformed attribute list")),
+            }
+        }
+    }
+    Ok((attrs, i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{examples::EXAMPLES, execute, format::graph};
+
+    // the lines of the rules of a code, in any order
+    fn rules(code: &str) -> Vec<&str> {
+        let mut rules: Vec<&str> = code.lines().filter(|l| l.contains('{')).collect();
+        rules.sort();
+        rules
+    }
+
+    #[test]
+    fn exported_machines_import_with_the_same_rules_and_layout() {
+        let example = EXAMPLES.iter().find(|e| e.id == "palindrome").unwrap();
+        let (tm, states) = graph(example.code);
+
+        let imported = import(&export(&tm, &states)).unwrap();
+        assert!(imported.warnings.is_empty());
+        for state in states.values() {
+            assert_eq!(imported.positions.get(&state.name), Some(&state.position));
+        }
+
+        assert_eq!(rules(&imported.code), rules(&source::to_source(&tm)));
+
+        let copy = source::parse(&imported.code).unwrap();
+        assert_eq!(source::state_name(&copy, 0), source::state_name(&tm, 0));
+        for word in execute::words(&['a', 'b'], 4) {
+            assert_eq!(
+                execute::run(&tm, &word, 1000).unwrap().outcome,
+                execute::run(&copy, &word, 1000).unwrap().outcome,
+                "{}",
+                word
+            );
+        }
+    }
+
+    #[test]
+    fn node_names_are_sanitized() {
+        let imported = import("digraph { \"q 0\" -> \"q{1}\" [label=\"a -> R\"]; \"q{1}\" [shape=doublecircle]; }").unwrap();
+        assert!(imported.code.contains("accepting: q_1_;"));
+        assert!(imported.code.contains("q_0 {a -> R} q_1_;"));
+        assert_eq!(imported.warnings.len(), 2);
+        source::parse(&imported.code).unwrap();
+    }
+
+    #[test]
+    fn unknown_characters_are_refused() {
+        assert!(tokenize("digraph { a -> b; }").is_ok());
+        assert!(tokenize("digraph { a -> b [label=\"!\"]; }").is_ok());
+        assert!(tokenize("digraph { a -> b ! }").is_err());
+    }
+}
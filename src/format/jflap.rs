@@ -0,0 +1,393 @@
+use std::collections::{BTreeSet, HashMap};
+
+use egui::Pos2;
+use roxmltree::{Document, Node};
+use turingrs::{turing_machine::TuringMachine, turing_state::TuringDirection};
+
+use crate::{
+    source::{self, ACCEPTING, BLANK},
+    ui::turing::State,
+};
+
+use super::{ImportedMachine, input_prologue, sanitize};
+
+/// Import a JFLAP Turing machine (`.jff`), single or multi-tape.
+///
+/// JFLAP tapes are read-write while the read ribbon of this app is read-only, so JFLAP tape
+/// `i` becomes write ribbon `i` and a prologue copies the input from the read ribbon to the
+/// first write ribbon before handing over to the JFLAP initial state.
+pub fn import(text: &str) -> Result<ImportedMachine, String> {
+    let doc = Document::parse(text).map_err(|e| e.to_string())?;
+    let structure = doc.root_element();
+    let mut imported = ImportedMachine::default();
+
+    let kind = child_text(structure, "type").unwrap_or_default();
+    if kind != "turing" {
+        return Err(format!("Expected a turing machine, found '{}'", kind));
+    }
+
+    let tapes: usize = child_text(structure, "tapes")
+        .and_then(|t| t.trim().parse().ok())
+        .unwrap_or(1);
+
+    // states may be inside an automaton element or directly in the structure
+    let automaton = structure
+        .children()
+        .find(|n| n.has_tag_name("automaton"))
+        .unwrap_or(structure);
+
+    let mut names: HashMap<String, String> = HashMap::new();
+    let mut initial: Option<String> = None;
+    let mut accepting = vec![];
+    let mut order = vec![];
+
+    for node in automaton.children().filter(|n| n.is_element()) {
+        match node.tag_name().name() {
+            "state" => {
+                let id = node.attribute("id").unwrap_or_default().to_string();
+                let name = node
+                    .attribute("name")
+                    .map(sanitize)
+                    .unwrap_or_else(|| format!("q{}", id));
+
+                if let (Some(x), Some(y)) = (
+                    child_text(node, "x").and_then(|x| x.trim().parse::<f32>().ok()),
+                    child_text(node, "y").and_then(|y| y.trim().parse::<f32>().ok()),
+                ) {
+                    imported.positions.insert(name.clone(), Pos2::new(x, y));
+                }
+                if node.children().any(|n| n.has_tag_name("initial")) {
+                    initial = Some(name.clone());
+                }
+                if node.children().any(|n| n.has_tag_name("final")) {
+                    accepting.push(name.clone());
+                }
+                order.push(name.clone());
+                names.insert(id, name);
+            }
+            "block" => imported.warnings.push(format!(
+                "Building block '{}' is not supported and was ignored",
+                node.attribute("name").unwrap_or_default()
+            )),
+            "transition" | "note" => {}
+            other => imported
+                .warnings
+                .push(format!("Unsupported element <{}> was ignored", other)),
+        }
+    }
+
+    let initial = initial
+        .or_else(|| order.first().cloned())
+        .ok_or("The machine has no state")?;
+
+    // read the transitions of every tape
+    let mut rules: Vec<(String, String, Vec<(char, char, TuringDirection)>)> = vec![];
+    let mut alphabet: BTreeSet<char> = BTreeSet::new();
+
+    for node in automaton.children().filter(|n| n.has_tag_name("transition")) {
+        let (Some(from), Some(to)) = (
+            child_text(node, "from").and_then(|id| names.get(id.trim())),
+            child_text(node, "to").and_then(|id| names.get(id.trim())),
+        ) else {
+            imported
+                .warnings
+                .push(String::from("Transition from or to a building block was ignored"));
+            continue;
+        };
+
+        let mut tapes_rule = vec![];
+        for tape in 1..=tapes {
+            let read = tape_text(node, "read", tape);
+            let write = tape_text(node, "write", tape);
+            let dir = match tape_text(node, "move", tape).as_str() {
+                "L" => TuringDirection::Left,
+                "R" => TuringDirection::Right,
+                _ => TuringDirection::None,
+            };
+
+            if read.chars().count() > 1 || write.chars().count() > 1 || read.starts_with('!') {
+                imported.warnings.push(format!(
+                    "Transition {} -> {} uses JFLAP specific symbols '{}'/'{}' and was ignored",
+                    from, to, read, write
+                ));
+                tapes_rule.clear();
+                break;
+            }
+
+            let read = read.chars().next().unwrap_or(BLANK);
+            let write = write.chars().next().unwrap_or(BLANK);
+            for c in [read, write] {
+                if c != '~' && c != BLANK {
+                    alphabet.insert(c);
+                }
+            }
+            tapes_rule.push((read, write, dir));
+        }
+
+        if !tapes_rule.is_empty() {
+            rules.push((from.clone(), to.clone(), tapes_rule));
+        }
+    }
+
+    if rules.iter().any(|(_, _, t)| t.iter().any(|(r, w, _)| *r == '~' || *w == '~')) {
+        imported.warnings.push(String::from(
+            "JFLAP '~' wildcards were expanded over the tape alphabet",
+        ));
+    }
+    imported.warnings.push(String::from(
+        "JFLAP tapes are read-write: the input is first copied to the first write ribbon",
+    ));
+
+    let mut lines = vec![];
+    if !accepting.is_empty() {
+        lines.push(format!("{}: {};", ACCEPTING, accepting.join(", ")));
+        lines.push(String::new());
+    }
+
+    let mut symbols: Vec<char> = alphabet.iter().copied().collect();
+    symbols.push(BLANK);
+    lines.extend(input_prologue(&alphabet, tapes, &initial));
+
+    // JFLAP rules, the read ribbon stays on the blank after the input
+    for (from, to, tapes_rule) in rules.iter() {
+        for expanded in expand_wildcards(tapes_rule, &symbols) {
+            let mut read = vec![BLANK];
+            let mut write = vec![];
+            for (r, w, d) in expanded {
+                read.push(r);
+                write.push((w, d));
+            }
+            lines.push(source::rule_line(
+                from,
+                &source::format_rule(&read, &TuringDirection::None, &write),
+                to,
+            ));
+        }
+    }
+
+    imported.code = lines.join("\n");
+    source::parse(&imported.code)?;
+
+    Ok(imported)
+}
+
+/// Export the machine to a JFLAP Turing machine. The read ribbon becomes the first tape and
+/// every write ribbon an additional tape.
+pub fn export(tm: &TuringMachine, states: &HashMap<u8, State>) -> String {
+    let tapes = tm.k as usize + 1;
+    let mut lines = vec![
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>"),
+        String::from("<structure>"),
+        String::from("\t<type>turing</type>"),
+    ];
+    if tapes > 1 {
+        lines.push(format!("\t<tapes>{}</tapes>", tapes));
+    }
+    lines.push(String::from("\t<automaton>"));
+
+    let mut indexes: Vec<u8> = states.keys().copied().collect();
+    indexes.sort();
+
+    for index in indexes.iter() {
+        let state = &states[index];
+        lines.push(format!(
+            "\t\t<state id=\"{}\" name=\"{}\">",
+            index,
+            escape(&state.name)
+        ));
+        lines.push(format!("\t\t\t<x>{}</x>", state.position.x));
+        lines.push(format!("\t\t\t<y>{}</y>", state.position.y));
+        if *index == 0 {
+            lines.push(String::from("\t\t\t<initial/>"));
+        }
+        if tm.get_state(*index).is_final {
+            lines.push(String::from("\t\t\t<final/>"));
+        }
+        lines.push(String::from("\t\t</state>"));
+    }
+
+    for index in indexes.iter() {
+        for t in tm.get_state(*index).transitions.iter() {
+            // rules created on the graph but not filled yet
+            if t.chars_read.len() != tapes {
+                continue;
+            }
+
+            lines.push(String::from("\t\t<transition>"));
+            lines.push(format!("\t\t\t<from>{}</from>", index));
+            lines.push(format!("\t\t\t<to>{}</to>", t.index_to_state));
+
+            // the read ribbon is never written, it writes back what it reads
+            let mut tapes_rule = vec![(t.chars_read[0], t.chars_read[0], &t.move_read)];
+            for (i, (w, d)) in t.chars_write.iter().enumerate() {
+                tapes_rule.push((t.chars_read[i + 1], *w, d));
+            }
+
+            for (i, (r, w, d)) in tapes_rule.into_iter().enumerate() {
+                let tape = if tapes > 1 {
+                    format!(" tape=\"{}\"", i + 1)
+                } else {
+                    String::new()
+                };
+                lines.push(symbol_element("read", &tape, r));
+                lines.push(symbol_element("write", &tape, w));
+                lines.push(format!(
+                    "\t\t\t<move{}>{}</move>",
+                    tape,
+                    match d {
+                        TuringDirection::Left => "L",
+                        TuringDirection::Right => "R",
+                        TuringDirection::None => "S",
+                    }
+                ));
+            }
+            lines.push(String::from("\t\t</transition>"));
+        }
+    }
+
+    lines.push(String::from("\t</automaton>"));
+    lines.push(String::from("</structure>"));
+    lines.join("\n")
+}
+
+// expand the '~' wildcards, reading anything and writing back what was read
+fn expand_wildcards(
+    rule: &[(char, char, TuringDirection)],
+    symbols: &[char],
+) -> Vec<Vec<(char, char, TuringDirection)>> {
+    let mut res: Vec<Vec<(char, char, TuringDirection)>> = vec![vec![]];
+    for (r, w, d) in rule.iter() {
+        let reads: Vec<char> = if *r == '~' { symbols.to_vec() } else { vec![*r] };
+        let mut next = vec![];
+        for prefix in res.iter() {
+            for c in reads.iter() {
+                let mut p = prefix.clone();
+                p.push((*c, if *w == '~' { *c } else { *w }, d.clone()));
+                next.push(p);
+            }
+        }
+        res = next;
+    }
+    res
+}
+
+// text of the first child with the given tag
+fn child_text<'a>(node: Node<'a, '_>, tag: &str) -> Option<&'a str> {
+    node.children()
+        .find(|n| n.has_tag_name(tag))
+        .map(|n| n.text().unwrap_or_default())
+}
+
+// text of a read/write/move element of a tape, tape 1 is the only one without attribute
+fn tape_text(node: Node<'_, '_>, tag: &str, tape: usize) -> String {
+    node.children()
+        .find(|n| {
+            n.has_tag_name(tag)
+                && match n.attribute("tape") {
+                    Some(t) => t.trim() == tape.to_string(),
+                    None => tape == 1,
+                }
+        })
+        .and_then(|n| n.text())
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+// xml element of a symbol, the blank being an empty element
+fn symbol_element(tag: &str, tape: &str, c: char) -> String {
+    if c == BLANK {
+        format!("\t\t\t<{}{}/>", tag, tape)
+    } else {
+        format!("\t\t\t<{}{}>{}</{}>", tag, tape, escape(&c.to_string()), tag)
+    }
+}
+
+// escape xml special characters
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        execute::{self, Outcome},
+        format::graph,
+    };
+
+    // accepts the words of a
+    const CODE: &str = "accepting: done;\n\n\
+        start {a -> R} start;\n\
+        start {_ -> L} done;";
+
+    // accepts the words starting with a, whatever follows it
+    const WILDCARDS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<structure>
+	<type>turing</type>
+	<automaton>
+		<state id="0" name="q0"><initial/></state>
+		<state id="1" name="q1"></state>
+		<state id="2" name="q2"><final/></state>
+		<transition><from>0</from><to>1</to><read>a</read><write>b</write><move>R</move></transition>
+		<transition><from>1</from><to>2</to><read>~</read><write>~</write><move>S</move></transition>
+	</automaton>
+</structure>"#;
+
+    #[test]
+    fn exported_machines_import_through_the_prologue() {
+        let (tm, states) = graph(CODE);
+        let imported = import(&export(&tm, &states)).unwrap();
+        for state in states.values() {
+            assert_eq!(imported.positions.get(&state.name), Some(&state.position));
+        }
+
+        let copy = source::parse(&imported.code).unwrap();
+        assert_eq!(copy.k, 1);
+        for word in execute::words(&['a', 'b'], 4) {
+            assert_eq!(
+                execute::run(&tm, &word, 1000).unwrap().outcome,
+                execute::run(&copy, &word, 1000).unwrap().outcome,
+                "{}",
+                word
+            );
+        }
+    }
+
+    #[test]
+    fn wildcards_read_every_symbol_and_write_it_back() {
+        let rule = [('~', '~', TuringDirection::Right), ('a', '~', TuringDirection::Left)];
+        let expanded = expand_wildcards(&rule, &['a', 'b', BLANK]);
+        let symbols: Vec<Vec<(char, char)>> =
+            expanded.iter().map(|r| r.iter().map(|(r, w, _)| (*r, *w)).collect()).collect();
+        assert_eq!(
+            symbols,
+            vec![
+                vec![('a', 'a'), ('a', 'a')],
+                vec![('b', 'b'), ('a', 'a')],
+                vec![(BLANK, BLANK), ('a', 'a')],
+            ]
+        );
+        assert!(expanded.iter().all(|r| matches!(r[1].2, TuringDirection::Left)));
+    }
+
+    #[test]
+    fn imported_wildcards_match_the_tape_alphabet() {
+        let imported = import(WILDCARDS).unwrap();
+        assert!(imported.warnings.iter().any(|w| w.contains("'~'")));
+
+        let tm = source::parse(&imported.code).unwrap();
+        let expected = [
+            ("a", Outcome::Accepted),
+            ("ab", Outcome::Accepted),
+            ("b", Outcome::Refused),
+            ("", Outcome::Refused),
+        ];
+        for (word, outcome) in expected {
+            assert_eq!(execute::run(&tm, word, 1000).unwrap().outcome, outcome, "{}", word);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use egui::{Pos2, Vec2};
+use itertools::Itertools;
+use turingrs::turing_machine::TuringMachine;
+
+use crate::{source, ui::turing::State, utils};
+
+/// Graph pixels per TikZ centimeter
+const SCALE: f32 = 100.0;
+
+/// Export the state diagram as a TikZ `automata` picture.
+///
+/// Rules sharing the same source and target are stacked on one edge, self transitions become
+/// loops pointing away from the center of the graph and pairs of opposite edges are bent so
+/// they do not overlap.
+pub fn export(tm: &TuringMachine, states: &HashMap<u8, State>) -> String {
+    let mut lines = vec![
+        String::from("% \\usepackage{tikz}"),
+        String::from("% \\usetikzlibrary{automata, arrows.meta, positioning}"),
+        String::from("\\begin{tikzpicture}[->, >={Stealth[round]}, shorten >=1pt, auto, semithick]"),
+    ];
+
+    let mut indexes: Vec<u8> = states.keys().copied().collect();
+    indexes.sort();
+
+    let center = indexes
+        .iter()
+        .fold(Vec2::ZERO, |acc, i| acc + states[i].position.to_vec2())
+        / indexes.len().max(1) as f32;
+
+    for index in indexes.iter() {
+        let state = &states[index];
+        let mut style = vec!["state"];
+        if *index == 0 {
+            style.push("initial");
+        }
+        if tm.get_state(*index).is_final {
+            style.push("accepting");
+        }
+        lines.push(format!(
+            "  \\node[{}] (s{}) at ({:.2}, {:.2}) {{{}}};",
+            style.join(", "),
+            index,
+            state.position.x / SCALE,
+            -state.position.y / SCALE,
+            escape(&state.name)
+        ));
+    }
+
+    // group the rules by source and target
+    let mut edges: Vec<((u8, u8), Vec<String>)> = vec![];
+    for index in indexes.iter() {
+        for t in tm.get_state(*index).transitions.iter() {
+            let key = (*index, t.index_to_state);
+            let text = format!("\\texttt{{{}}}", escape(&source::rule_body(t)));
+            match edges.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, rules)) => rules.push(text),
+                None => edges.push((key, vec![text])),
+            }
+        }
+    }
+
+    if !edges.is_empty() {
+        lines.push(String::from("  \\path"));
+        for ((from, to), rules) in edges.iter() {
+            let style = if from == to {
+                loop_side(states[from].position, center)
+            } else if edges.iter().any(|(k, _)| *k == (*to, *from)) {
+                "bend left"
+            } else {
+                ""
+            };
+
+            let label = if rules.len() > 1 {
+                format!("node[align=center] {{{}}}", rules.iter().join(" \\\\ "))
+            } else {
+                format!("node {{{}}}", rules[0])
+            };
+
+            lines.push(format!(
+                "    (s{}) edge{} {} (s{})",
+                from,
+                if style.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", style)
+                },
+                label,
+                to
+            ));
+        }
+        lines.push(String::from("  ;"));
+    }
+
+    lines.push(String::from("\\end{tikzpicture}"));
+    lines.join("\n")
+}
+
+// pick the side of the loop, away from the center of the graph like on the graph panel
+fn loop_side(pos: Pos2, center: Vec2) -> &'static str {
+    let dir = utils::direction(center.to_pos2(), pos);
+    if !dir.x.is_finite() || !dir.y.is_finite() {
+        "loop above"
+    } else if dir.y.abs() >= dir.x.abs() {
+        // the y axis of the screen points down
+        if dir.y <= 0.0 { "loop above" } else { "loop below" }
+    } else if dir.x < 0.0 {
+        "loop left"
+    } else {
+        "loop right"
+    }
+}
+
+// escape the characters with a special meaning in LaTeX
+fn escape(text: &str) -> String {
+    let mut res = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => res.push_str("\\textbackslash{}"),
+            '{' | '}' | '$' | '&' | '#' | '%' | '_' => {
+                res.push('\\');
+                res.push(c);
+            }
+            '^' => res.push_str("\\textasciicircum{}"),
+            '~' => res.push_str("\\textasciitilde{}"),
+            '<' => res.push_str("\\textless{}"),
+            '>' => res.push_str("\\textgreater{}"),
+            _ => res.push(c),
+        }
+    }
+    res
+}
@@ -0,0 +1,335 @@
+use std::collections::BTreeSet;
+
+use turingrs::{turing_machine::TuringMachine, turing_state::TuringDirection};
+use yaml_rust2::{Yaml, YamlLoader};
+
+use crate::source::{self, ACCEPTING, BLANK};
+
+use super::{ImportedMachine, input_prologue, sanitize};
+
+/// Import a turingmachine.io machine.
+///
+/// The single read-write tape becomes the first write ribbon, filled from the input by a
+/// prologue. States without any action are halting states and are accepting, unless their
+/// name contains "reject".
+pub fn import(text: &str) -> Result<ImportedMachine, String> {
+    let docs = YamlLoader::load_from_str(text).map_err(|e| e.to_string())?;
+    let doc = docs.first().ok_or("The document is empty")?;
+    let mut imported = ImportedMachine::default();
+
+    let blank = match &doc["blank"] {
+        Yaml::BadValue => return Err(String::from("Missing 'blank' symbol")),
+        b => symbol(b)?,
+    };
+    let start = name(&doc["start state"]).map_err(|_| "Missing 'start state'")?;
+    let table = doc["table"].as_hash().ok_or("Missing 'table'")?;
+
+    // replace the machine blank by the blank of the code
+    let to_code = |c: char| if c == blank { BLANK } else { c };
+
+    if let Some(input) = scalar(&doc["input"]) {
+        imported.input = Some(input.chars().map(to_code).collect());
+    }
+
+    let mut accepting = vec![];
+    let mut alphabet: BTreeSet<char> = BTreeSet::new();
+    let mut rules = vec![];
+
+    for (state, actions) in table.iter() {
+        let state = name(state)?;
+
+        let Some(actions) = actions.as_hash().filter(|a| !a.is_empty()) else {
+            if !state.to_lowercase().contains("reject") {
+                accepting.push(state.clone());
+            }
+            continue;
+        };
+
+        for (symbols, action) in actions.iter() {
+            let read: Vec<char> = match symbols {
+                Yaml::Array(list) => list.iter().map(symbol).collect::<Result<_, _>>()?,
+                s => vec![symbol(s)?],
+            };
+            let (write, direction, next) = parse_action(action)?;
+
+            for r in read {
+                let w = write.unwrap_or(r);
+                alphabet.extend([r, w].into_iter().filter(|c| *c != blank));
+                rules.push((
+                    state.clone(),
+                    to_code(r),
+                    to_code(w),
+                    direction.clone(),
+                    next.clone().unwrap_or_else(|| state.clone()),
+                ));
+            }
+        }
+    }
+
+    if alphabet.remove(&BLANK) {
+        imported.warnings.push(format!(
+            "Symbol '{}' is the blank of the code, it is read as a blank",
+            BLANK
+        ));
+    }
+    if let Some(input) = &imported.input {
+        alphabet.extend(input.chars().filter(|c| *c != BLANK));
+    }
+
+    let mut lines = vec![];
+    if !accepting.is_empty() {
+        lines.push(format!("{}: {};", ACCEPTING, accepting.join(", ")));
+        lines.push(String::new());
+    }
+    lines.extend(input_prologue(&alphabet, 1, &start));
+
+    for (from, r, w, d, to) in rules.iter() {
+        lines.push(source::rule_line(
+            from,
+            &source::format_rule(&[BLANK, *r], &TuringDirection::None, &[(*w, d.clone())]),
+            to,
+        ));
+    }
+
+    imported.code = lines.join("\n");
+    source::parse(&imported.code)?;
+
+    Ok(imported)
+}
+
+/// Export a machine without write ribbons to turingmachine.io, with the current input.
+///
+/// turingmachine.io only halts, so halting states are written the way `import` reads them:
+/// dead ends that refuse get "reject" in their name, and the symbols an accepting state has
+/// no rule for move to an added accepting dead end.
+pub fn export(tm: &TuringMachine, input: &str) -> Result<String, String> {
+    if tm.k != 0 {
+        return Err(String::from(
+            "turingmachine.io only supports single tape machines, without write ribbons",
+        ));
+    }
+
+    let count = tm.states.len() as u8;
+    let mut taken: BTreeSet<String> = (0..count).map(|i| source::state_name(tm, i)).collect();
+    let mut unique = |name: String| {
+        let mut name = name;
+        while taken.contains(&name) {
+            name.push('_');
+        }
+        taken.insert(name.clone());
+        name
+    };
+
+    // every symbol that can be on the tape
+    let mut alphabet: BTreeSet<char> = input.chars().collect();
+    alphabet.insert(BLANK);
+
+    let mut names = vec![];
+    for index in 0..count {
+        let state = tm.get_state(index);
+        let name = source::state_name(tm, index);
+        let rejecting = name.to_lowercase().contains("reject");
+        alphabet.extend(state.transitions.iter().filter_map(|t| t.chars_read.first()));
+
+        names.push(match (state.transitions.is_empty(), state.is_final) {
+            (true, false) if !rejecting => unique(format!("{}_reject", name)),
+            (true, true) if rejecting => {
+                return Err(format!(
+                    "State '{}' accepts but turingmachine.io refuses in the states named reject",
+                    name
+                ));
+            }
+            _ => name,
+        });
+    }
+    let accept = unique(String::from("accept"));
+    let mut accept_used = false;
+
+    let mut lines = vec![
+        format!("input: {}", quote(&input.chars().map(from_code).collect::<String>())),
+        String::from("blank: ' '"),
+        format!("start state: {}", quote(&names[0])),
+        String::from("table:"),
+    ];
+
+    for index in 0..count {
+        let state = tm.get_state(index);
+        lines.push(format!("  {}:", quote(&names[index as usize])));
+
+        for t in state.transitions.iter() {
+            let Some(read) = t.chars_read.first() else {
+                continue;
+            };
+            let direction = match t.move_read {
+                TuringDirection::Left => "L",
+                TuringDirection::Right => "R",
+                TuringDirection::None => {
+                    return Err(format!(
+                        "State '{}' has a rule that does not move, not supported by turingmachine.io",
+                        source::state_name(tm, index)
+                    ));
+                }
+            };
+            lines.push(format!(
+                "    {}: {{{}: {}}}",
+                quote(&from_code(*read).to_string()),
+                direction,
+                quote(&names[t.index_to_state as usize])
+            ));
+        }
+
+        // an accepting state with rules accepts on the symbols it has no rule for
+        if state.is_final && !state.transitions.is_empty() {
+            let read: BTreeSet<char> = state.transitions.iter().filter_map(|t| t.chars_read.first()).copied().collect();
+            for c in alphabet.difference(&read) {
+                lines.push(format!("    {}: {{R: {}}}", quote(&from_code(*c).to_string()), quote(&accept)));
+                accept_used = true;
+            }
+        }
+    }
+    if accept_used {
+        lines.push(format!("  {}:", quote(&accept)));
+    }
+
+    Ok(lines.join("\n"))
+}
+
+// parse an action: "L", "R", or a mapping with an optional write and a move to a state
+fn parse_action(
+    action: &Yaml,
+) -> Result<(Option<char>, TuringDirection, Option<String>), String> {
+    if let Some(d) = action.as_str() {
+        return Ok((None, direction(d)?, None));
+    }
+
+    let hash = action
+        .as_hash()
+        .ok_or_else(|| format!("Invalid action {:?}", action))?;
+    let mut write = None;
+    let mut moves = None;
+
+    for (key, value) in hash.iter() {
+        match key.as_str() {
+            Some("write") => write = Some(symbol(value)?),
+            Some(d) => {
+                let next = if value.is_null() { None } else { Some(name(value)?) };
+                moves = Some((direction(d)?, next));
+            }
+            None => return Err(format!("Invalid action key {:?}", key)),
+        }
+    }
+
+    let (d, next) = moves.ok_or("An action must move the head left or right")?;
+    Ok((write, d, next))
+}
+
+fn direction(text: &str) -> Result<TuringDirection, String> {
+    match text {
+        "L" => Ok(TuringDirection::Left),
+        "R" => Ok(TuringDirection::Right),
+        _ => Err(format!("Unknown move '{}'", text)),
+    }
+}
+
+// text of a scalar value, numbers being written as is
+fn scalar(value: &Yaml) -> Option<String> {
+    match value {
+        Yaml::String(s) | Yaml::Real(s) => Some(s.clone()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn symbol(value: &Yaml) -> Result<char, String> {
+    let text = scalar(value).ok_or_else(|| format!("Invalid symbol {:?}", value))?;
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(format!("Symbol '{}' must be a single character", text)),
+    }
+}
+
+fn name(value: &Yaml) -> Result<String, String> {
+    scalar(value)
+        .map(|s| sanitize(&s))
+        .ok_or_else(|| format!("Invalid state name {:?}", value))
+}
+
+fn from_code(c: char) -> char {
+    if c == BLANK { ' ' } else { c }
+}
+
+// single quoted yaml string
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execute;
+
+    // accepts the words of a, reading a b or going left of the input refuses
+    const CODE: &str = "accepting: done;\n\n\
+        start {a -> R} start;\n\
+        start {_ -> L} done;";
+
+    #[test]
+    fn exported_machines_import_with_the_same_verdicts() {
+        let tm = source::parse(CODE).unwrap();
+        let imported = import(&export(&tm, "aa").unwrap()).unwrap();
+        assert_eq!(imported.input.as_deref(), Some("aa"));
+
+        let copy = source::parse(&imported.code).unwrap();
+        for word in execute::words(&['a', 'b'], 4) {
+            assert_eq!(
+                execute::run(&tm, &word, 1000).unwrap().outcome,
+                execute::run(&copy, &word, 1000).unwrap().outcome,
+                "{}",
+                word
+            );
+        }
+    }
+
+    // accepts in done and in more, which has rules, and refuses in stuck, a dead end
+    const HALTING: &str = "accepting: done, more;\n\n\
+        start {a -> R} start;\n\
+        start {b -> R} more;\n\
+        start {c -> R} stuck;\n\
+        start {_ -> L} done;\n\
+        more {a -> R} start;";
+
+    #[test]
+    fn halting_states_keep_their_verdicts() {
+        let tm = source::parse(HALTING).unwrap();
+        let imported = import(&export(&tm, "").unwrap()).unwrap();
+        assert!(imported.code.contains("stuck_reject"));
+
+        let copy = source::parse(&imported.code).unwrap();
+        for word in execute::words(&['a', 'b', 'c'], 3) {
+            assert_eq!(
+                execute::run(&tm, &word, 1000).unwrap().outcome,
+                execute::run(&copy, &word, 1000).unwrap().outcome,
+                "{}",
+                word
+            );
+        }
+    }
+
+    #[test]
+    fn accepting_states_named_reject_are_refused() {
+        let tm = source::parse("accepting: reject;\n\nstart {a -> R} reject;").unwrap();
+        assert!(export(&tm, "").is_err());
+    }
+
+    #[test]
+    fn state_names_are_sanitized() {
+        let text = "input: 'a'\nblank: ' '\nstart state: 'q {0}'\ntable:\n  'q {0}':\n    a: {R: 'q->1'}\n  'q->1':\n";
+        let imported = import(text).unwrap();
+        let tm = source::parse(&imported.code).unwrap();
+        assert!(imported.code.contains("q__0_"));
+        assert!(imported.code.contains("accepting: q__1;"));
+        assert_eq!(execute::run(&tm, "a", 100).unwrap().outcome, execute::Outcome::Accepted);
+    }
+}
@@ -0,0 +1,472 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+use turingrs::turing_machine::TuringMachine;
+
+use crate::{
+    execute::{self, Outcome},
+    profile,
+};
+
+/// What decides whether an input should be accepted
+pub enum Oracle {
+    /// Inputs matching a regular expression as a whole
+    Regex(Regex),
+    /// Inputs of a list, every other input being refused
+    Accepted(HashSet<String>),
+    /// Inputs accepted by a reference machine
+    Machine(TuringMachine),
+}
+
+impl Oracle {
+    /// Regular expression matching whole inputs
+    pub fn regex(pattern: &str) -> Result<Oracle, String> {
+        Regex::new(&format!("^(?:{})$", pattern))
+            .map(Oracle::Regex)
+            .map_err(|e| format!("Invalid regular expression: {}", e))
+    }
+
+    /// List of accepted inputs, one per line, ε being the empty input
+    pub fn accepted(list: &str) -> Oracle {
+        Oracle::Accepted(
+            list.lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(|l| if l == "ε" { String::new() } else { l.to_string() })
+                .collect(),
+        )
+    }
+
+    /// Whether the input should be accepted, unknown when the reference machine runs out of budget,
+    /// with the number of steps the reference machine ran
+    pub fn expects(&self, input: &str, budget: usize) -> Result<(Option<bool>, usize), String> {
+        Ok(match self {
+            Oracle::Regex(regex) => (Some(regex.is_match(input)), 0),
+            Oracle::Accepted(list) => (Some(list.contains(input)), 0),
+            Oracle::Machine(tm) => {
+                let run = execute::run(tm, input, budget)?;
+                let expected = match run.outcome {
+                    Outcome::Accepted => Some(true),
+                    Outcome::Refused => Some(false),
+                    Outcome::Timeout => None,
+                };
+                (expected, run.steps)
+            }
+        })
+    }
+}
+
+/// An input on which the machine disagrees with the oracle
+#[derive(Clone, Debug)]
+pub struct Failure {
+    pub input: String,
+    /// Input generated before shrinking
+    pub original: String,
+    pub expected: bool,
+}
+
+/// Result of a fuzzing campaign
+#[derive(Clone, Debug, Default)]
+pub struct Campaign {
+    pub runs: usize,
+    /// Runs where the machine or the oracle ran out of budget
+    pub timeouts: usize,
+    pub failures: Vec<Failure>,
+    /// The campaign was stopped before the end
+    pub stopped: bool,
+}
+
+/// A fuzzing campaign going on, advanced a slice at a time to keep the interface responsive
+pub struct Fuzzer {
+    tm: TuringMachine,
+    oracle: Oracle,
+    alphabet: Vec<char>,
+    runs: usize,
+    max_length: usize,
+    budget: usize,
+    seen: HashSet<String>,
+    shrinking: Option<Shrinking>,
+    done: bool,
+    pub campaign: Campaign,
+}
+
+impl Fuzzer {
+    /// Run the machine on random inputs over an alphabet and check its verdicts against an oracle,
+    /// shrinking every failing input to a minimal one
+    pub fn new(
+        tm: &TuringMachine,
+        oracle: Oracle,
+        alphabet: &[char],
+        runs: usize,
+        max_length: usize,
+        budget: usize,
+    ) -> Result<Self, String> {
+        if alphabet.is_empty() {
+            return Err(String::from("The alphabet is empty"));
+        }
+        Ok(Fuzzer {
+            tm: tm.clone(),
+            oracle,
+            alphabet: alphabet.to_vec(),
+            runs,
+            max_length,
+            budget,
+            seen: HashSet::new(),
+            shrinking: None,
+            done: false,
+            campaign: Campaign::default(),
+        })
+    }
+
+    /// Number of runs of the campaign
+    pub fn runs(&self) -> usize {
+        self.runs
+    }
+
+    /// Whether every input was run and every failure shrunk, or the campaign stopped
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    /// Stop the campaign, the failures shrunk so far being kept
+    pub fn stop(&mut self) {
+        self.campaign.stopped |= !self.done;
+        self.done = true;
+    }
+
+    /// Run inputs or shrink a failure until about `work` steps were run,
+    /// returning whether the campaign is over
+    pub fn advance(&mut self, work: usize) -> Result<bool, String> {
+        let mut spent = 0;
+        while !self.done && spent < work {
+            match &mut self.shrinking {
+                Some(shrinking) => match shrinking.step(&self.tm, &self.oracle, &self.alphabet, self.budget)? {
+                    Some(steps) => spent += steps.max(1),
+                    None => {
+                        let failure = shrinking.failure();
+                        self.shrinking = None;
+                        if self.seen.insert(failure.input.clone()) {
+                            self.campaign.failures.push(failure);
+                            self.campaign.failures.sort_by_key(|f| (f.input.chars().count(), f.input.clone()));
+                        }
+                    }
+                },
+                None if self.campaign.runs == self.runs => self.done = true,
+                None => {
+                    // short inputs first, where most edge cases are
+                    let run = self.campaign.runs;
+                    let length = if self.max_length == 0 { 0 } else { run % (self.max_length + 1) };
+                    let input = profile::random_input(&self.alphabet, length);
+                    self.campaign.runs += 1;
+
+                    let (check, steps) = check(&self.tm, &self.oracle, &input, self.budget)?;
+                    spent += steps.max(1);
+                    match check {
+                        Check::Timeout => self.campaign.timeouts += 1,
+                        Check::Pass => {}
+                        Check::Fail(expected) => self.shrinking = Some(Shrinking::new(input, expected, &self.alphabet)),
+                    }
+                }
+            }
+        }
+        Ok(self.done)
+    }
+}
+
+enum Check {
+    Pass,
+    /// The verdict expected by the oracle
+    Fail(bool),
+    Timeout,
+}
+
+// run the machine on an input and compare its verdict with the oracle, with the steps run
+fn check(tm: &TuringMachine, oracle: &Oracle, input: &str, budget: usize) -> Result<(Check, usize), String> {
+    let run = execute::run(tm, input, budget)?;
+    let accepted = match run.outcome {
+        Outcome::Accepted => true,
+        Outcome::Refused => false,
+        Outcome::Timeout => return Ok((Check::Timeout, run.steps)),
+    };
+    let (expected, steps) = oracle.expects(input, budget)?;
+    let check = match expected {
+        Some(expected) if expected != accepted => Check::Fail(expected),
+        Some(_) => Check::Pass,
+        None => Check::Timeout,
+    };
+    Ok((check, run.steps + steps))
+}
+
+/// A failing input being shrunk: chunks of it are removed, then single symbols, then its symbols
+/// are replaced by the first ones of the alphabet, for as long as it keeps failing
+struct Shrinking {
+    original: String,
+    current: Vec<char>,
+    expected: bool,
+    /// Inputs to try on the current one, the next being tried at `next`
+    candidates: Vec<Vec<char>>,
+    next: usize,
+}
+
+impl Shrinking {
+    fn new(input: String, expected: bool, alphabet: &[char]) -> Self {
+        let current: Vec<char> = input.chars().collect();
+        let candidates = candidates(&current, alphabet);
+        Shrinking { original: input, current, expected, candidates, next: 0 }
+    }
+
+    // try the next candidate, returning the steps run, or None once no candidate fails
+    fn step(&mut self, tm: &TuringMachine, oracle: &Oracle, alphabet: &[char], budget: usize) -> Result<Option<usize>, String> {
+        let Some(candidate) = self.candidates.get(self.next) else {
+            return Ok(None);
+        };
+        let (check, steps) = check(tm, oracle, &candidate.iter().collect::<String>(), budget)?;
+        match check {
+            Check::Fail(expected) => {
+                (self.current, self.expected) = (candidate.clone(), expected);
+                self.candidates = candidates(&self.current, alphabet);
+                self.next = 0;
+            }
+            _ => self.next += 1,
+        }
+        Ok(Some(steps))
+    }
+
+    fn failure(&self) -> Failure {
+        Failure { input: self.current.iter().collect(), original: self.original.clone(), expected: self.expected }
+    }
+}
+
+// inputs a step smaller than the current one, in the order they are tried
+fn candidates(current: &[char], alphabet: &[char]) -> Vec<Vec<char>> {
+    let mut candidates = vec![];
+
+    let mut chunk = current.len().max(1);
+    while chunk > 0 {
+        for start in (0..current.len()).step_by(chunk) {
+            let mut candidate = current.to_vec();
+            candidate.drain(start..(start + chunk).min(current.len()));
+            candidates.push(candidate);
+        }
+        chunk /= 2;
+    }
+
+    for i in 0..current.len() {
+        for c in alphabet.iter().take_while(|c| **c != current[i]) {
+            let mut candidate = current.to_vec();
+            candidate[i] = *c;
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+/// Kind of oracle chosen by the user
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OracleKind {
+    Regex,
+    List,
+    Machine,
+}
+
+impl OracleKind {
+    pub const ALL: [OracleKind; 3] = [OracleKind::Regex, OracleKind::List, OracleKind::Machine];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OracleKind::Regex => "Regular expression",
+            OracleKind::List => "Accepted inputs",
+            OracleKind::Machine => "Reference machine",
+        }
+    }
+}
+
+/// A test saved in the code that the machine does not pass
+#[derive(Clone, Debug)]
+pub struct TestFailure {
+    pub input: String,
+    pub expected: bool,
+    pub outcome: Outcome,
+}
+
+/// The tests saved in the code being run, a slice at a time
+pub struct Tester {
+    tm: TuringMachine,
+    budget: usize,
+    pub tests: Vec<(String, bool)>,
+    /// Number of tests run so far
+    pub checked: usize,
+    pub failures: Vec<TestFailure>,
+    done: bool,
+}
+
+impl Tester {
+    /// Run tests, inputs and the verdicts they expect, each with a budget of steps
+    pub fn new(tm: &TuringMachine, tests: Vec<(String, bool)>, budget: usize) -> Self {
+        Tester { tm: tm.clone(), budget, tests, checked: 0, failures: vec![], done: false }
+    }
+
+    /// Whether every test was run or the run stopped
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    /// Stop running the tests, the failures found so far being kept
+    pub fn stop(&mut self) {
+        self.done = true;
+    }
+
+    /// Run tests until about `work` steps were run, returning whether every test was run
+    pub fn advance(&mut self, work: usize) -> Result<bool, String> {
+        let mut spent = 0;
+        while !self.done && spent < work {
+            let Some((input, expected)) = self.tests.get(self.checked) else {
+                self.done = true;
+                break;
+            };
+            let run = execute::run(&self.tm, input, self.budget)?;
+            self.checked += 1;
+            spent += run.steps.max(1);
+
+            if run.outcome != if *expected { Outcome::Accepted } else { Outcome::Refused } {
+                self.failures.push(TestFailure { input: input.clone(), expected: *expected, outcome: run.outcome });
+            }
+        }
+        Ok(self.done)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source;
+
+    // accepts the words without b
+    const NO_B: &str = "accepting: done;\n\n\
+        start {a, _ -> R, _, N} start;\n\
+        start {_, _ -> N, _, N} done;";
+
+    // never halts on a
+    const LOOP: &str = "accepting: done;\n\n\
+        start {a, _ -> N, _, N} start;\n\
+        start {_, _ -> N, _, N} done;";
+
+    fn verdict(oracle: &Oracle, input: &str) -> Option<bool> {
+        oracle.expects(input, 100).unwrap().0
+    }
+
+    // shrink a failing input at once
+    fn shrink(tm: &TuringMachine, oracle: &Oracle, alphabet: &[char], input: &str) -> Failure {
+        let (Check::Fail(expected), _) = check(tm, oracle, input, 100).unwrap() else {
+            panic!("'{}' does not fail", input);
+        };
+        let mut shrinking = Shrinking::new(input.to_string(), expected, alphabet);
+        while shrinking.step(tm, oracle, alphabet, 100).unwrap().is_some() {}
+        shrinking.failure()
+    }
+
+    // run a whole campaign at once
+    fn fuzz(tm: &TuringMachine, oracle: Oracle, alphabet: &[char], runs: usize, max_length: usize) -> Campaign {
+        let mut fuzzer = Fuzzer::new(tm, oracle, alphabet, runs, max_length, 100).unwrap();
+        while !fuzzer.advance(usize::MAX).unwrap() {}
+        fuzzer.campaign
+    }
+
+    #[test]
+    fn oracles_expect_verdicts() {
+        let regex = Oracle::regex("a*").unwrap();
+        assert_eq!(verdict(&regex, "aa"), Some(true));
+        assert_eq!(verdict(&regex, "ab"), Some(false));
+        assert!(Oracle::regex("(").is_err());
+
+        let list = Oracle::accepted("ε\n a \n\n");
+        assert_eq!(verdict(&list, ""), Some(true));
+        assert_eq!(verdict(&list, "a"), Some(true));
+        assert_eq!(verdict(&list, "b"), Some(false));
+
+        let machine = Oracle::Machine(source::parse(NO_B).unwrap());
+        assert_eq!(verdict(&machine, "aa"), Some(true));
+        assert_eq!(verdict(&machine, "ab"), Some(false));
+        // only a reference machine runs steps
+        assert!(machine.expects("aa", 100).unwrap().1 > 0);
+        assert_eq!(regex.expects("aa", 100), Ok((Some(true), 0)));
+
+        let looping = Oracle::Machine(source::parse(LOOP).unwrap());
+        assert_eq!(verdict(&looping, "a"), None);
+        assert_eq!(verdict(&looping, ""), Some(true));
+    }
+
+    #[test]
+    fn failing_inputs_are_shrunk() {
+        let tm = source::parse(NO_B).unwrap();
+        let oracle = Oracle::regex("a*b?").unwrap();
+
+        let failure = shrink(&tm, &oracle, &['a', 'b'], "aab");
+        assert_eq!(failure.input, "b");
+        assert_eq!(failure.original, "aab");
+        assert!(failure.expected);
+
+        // nothing to shrink on inputs that pass
+        assert!(matches!(check(&tm, &oracle, "aa", 100).unwrap().0, Check::Pass));
+    }
+
+    #[test]
+    fn symbols_are_replaced_by_the_first_ones_of_the_alphabet() {
+        let tm = source::parse(NO_B).unwrap();
+        // the words of a and the words of length 2 ending with b should be accepted
+        let oracle = Oracle::regex("a*|.b").unwrap();
+
+        let failure = shrink(&tm, &oracle, &['a', 'b'], "bb");
+        assert_eq!(failure.input, "ab");
+    }
+
+    #[test]
+    fn campaigns_report_shrunk_failures_once() {
+        let tm = source::parse(NO_B).unwrap();
+
+        let campaign = fuzz(&tm, Oracle::regex("a*b?").unwrap(), &['a', 'b'], 50, 4);
+        assert_eq!(campaign.runs, 50);
+        assert!(campaign.failures.len() <= 1);
+        assert!(campaign.failures.iter().all(|f| f.input == "b" && f.expected));
+
+        let campaign = fuzz(&tm, Oracle::regex("a*").unwrap(), &['a', 'b'], 50, 4);
+        assert!(campaign.failures.is_empty() && !campaign.stopped);
+
+        let looping = source::parse(LOOP).unwrap();
+        // the lengths alternate between 0 and 1, the machine loops on a
+        let campaign = fuzz(&looping, Oracle::regex("a?").unwrap(), &['a'], 10, 1);
+        assert_eq!(campaign.timeouts, 5);
+        assert!(campaign.failures.is_empty());
+        assert!(Fuzzer::new(&tm, Oracle::regex("").unwrap(), &[], 10, 4, 100).is_err());
+    }
+
+    #[test]
+    fn campaigns_advance_a_slice_at_a_time() {
+        let tm = source::parse(NO_B).unwrap();
+        let mut fuzzer = Fuzzer::new(&tm, Oracle::regex("a*").unwrap(), &['a'], 3, 2, 100).unwrap();
+        for runs in 1..=3 {
+            assert!(!fuzzer.advance(1).unwrap());
+            assert_eq!(fuzzer.campaign.runs, runs);
+        }
+        assert!(fuzzer.advance(1).unwrap());
+
+        let mut fuzzer = Fuzzer::new(&tm, Oracle::regex("a*").unwrap(), &['a'], 3, 2, 100).unwrap();
+        fuzzer.advance(1).unwrap();
+        fuzzer.stop();
+        assert!(fuzzer.done() && fuzzer.campaign.stopped);
+    }
+
+    #[test]
+    fn failing_tests_are_reported() {
+        let tm = source::parse(NO_B).unwrap();
+        let tests = vec![(String::from("aa"), true), (String::from("ab"), true), (String::from("b"), false)];
+        let mut tester = Tester::new(&tm, tests, 100);
+
+        assert!(!tester.advance(1).unwrap());
+        assert_eq!(tester.checked, 1);
+        while !tester.advance(usize::MAX).unwrap() {}
+        assert_eq!(tester.checked, 3);
+        assert_eq!(tester.failures.len(), 1);
+        assert_eq!((tester.failures[0].input.as_str(), tester.failures[0].outcome), ("ab", Outcome::Refused));
+    }
+}
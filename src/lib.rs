@@ -1,7 +1,22 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
+mod analysis;
 mod app;
+mod automaton;
+mod beaver;
+mod compose;
+mod equivalence;
+mod examples;
+mod execute;
+mod format;
+mod fuzz;
+mod ntm;
+mod profile;
+mod source;
+mod tape;
+mod transform;
 mod ui;
+mod universal;
 // mod turing;
 mod utils;
 pub use app::TuringApp;
\ No newline at end of file
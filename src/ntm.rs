@@ -0,0 +1,184 @@
+use std::collections::VecDeque;
+
+use turingrs::{
+    turing_machine::{TuringExecutionStep, TuringMachine},
+    turing_state::TuringTransition,
+};
+
+use crate::{source::BLANK, tape};
+
+/// What is known of a configuration of the exploration
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Status {
+    /// Not explored, the budget was exhausted before
+    Open,
+    /// Explored, with at least a rule to follow
+    Inner,
+    Accepted,
+    /// No rule applies and the state is not accepting
+    Refused,
+}
+
+/// A configuration of the computation tree
+#[derive(Clone)]
+pub struct Node {
+    pub state: u8,
+    pub step: TuringExecutionStep,
+    pub parent: Option<usize>,
+    pub depth: usize,
+    pub status: Status,
+}
+
+/// Computation tree of a machine explored breadth-first, nodes in exploration order
+pub struct Tree {
+    pub nodes: Vec<Node>,
+    /// First accepting node found, at the end of a shortest accepting path
+    pub accepting: Option<usize>,
+    /// Whether the budget stopped the exploration before the whole tree was explored
+    pub truncated: bool,
+}
+
+impl Tree {
+    /// States and steps from the root to a node
+    pub fn branch(&self, node: usize) -> Vec<(u8, TuringExecutionStep)> {
+        let mut branch = vec![];
+        let mut current = Some(node);
+        while let Some(i) = current {
+            branch.push((self.nodes[i].state, self.nodes[i].step.clone()));
+            current = self.nodes[i].parent;
+        }
+        branch.reverse();
+        branch
+    }
+}
+
+/// Explore every rule that applies in every configuration, breadth-first from the initial state,
+/// until a configuration halts in an accepting state or the depth or number of nodes is exceeded
+pub fn explore(tm: &TuringMachine, initial: &TuringExecutionStep, max_depth: usize, max_nodes: usize) -> Tree {
+    let mut tree = Tree {
+        nodes: vec![Node { state: 0, step: initial.clone(), parent: None, depth: 0, status: Status::Open }],
+        accepting: None,
+        truncated: false,
+    };
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(i) = queue.pop_front() {
+        let Node { state, depth, .. } = tree.nodes[i];
+
+        let read = tape::read(&tree.nodes[i].step);
+        let rules: Vec<&TuringTransition> = tm
+            .get_state(state)
+            .transitions
+            .iter()
+            .filter(|t| t.chars_read == read)
+            .collect();
+
+        // like the executor, a branch accepts when it halts in an accepting state
+        if rules.is_empty() && tm.get_state(state).is_final {
+            tree.nodes[i].status = Status::Accepted;
+            tree.accepting = Some(i);
+            // the other nodes left in the queue are not explored
+            tree.truncated |= !queue.is_empty();
+            break;
+        }
+        if rules.is_empty() {
+            tree.nodes[i].status = Status::Refused;
+            continue;
+        }
+        if depth >= max_depth || tree.nodes.len() + rules.len() > max_nodes {
+            tree.truncated = true;
+            continue;
+        }
+
+        tree.nodes[i].status = Status::Inner;
+        for t in rules {
+            let step = apply(&tree.nodes[i].step, t);
+            queue.push_back(tree.nodes.len());
+            tree.nodes.push(Node { state: t.index_to_state, step, parent: Some(i), depth: depth + 1, status: Status::Open });
+        }
+    }
+    tree
+}
+
+/// Configuration reached by following a rule: the symbols are written under the heads
+/// of the write ribbons, then every head moves
+pub fn apply(step: &TuringExecutionStep, transition: &TuringTransition) -> TuringExecutionStep {
+    let mut next = step.clone();
+    next.transition_taken = transition.clone();
+
+    let (chars, pointer) = tape::ribbon_mut(&mut next, 0);
+    tape::shift(chars, pointer, &transition.move_read);
+
+    for (i, (c, direction)) in transition.chars_write.iter().enumerate() {
+        let (chars, pointer) = tape::ribbon_mut(&mut next, i + 1);
+        if *pointer >= chars.len() {
+            chars.resize(*pointer + 1, BLANK);
+        }
+        chars[*pointer] = *c;
+        tape::shift(chars, pointer, direction);
+    }
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use turingrs::turing_machine::TuringMachineExecutor;
+
+    use super::*;
+    use crate::{
+        analysis, compose,
+        examples::EXAMPLES,
+        execute::{self, Outcome},
+        source,
+    };
+
+    // content of the write ribbons without the blanks around
+    fn output(step: &TuringExecutionStep) -> Vec<String> {
+        step.write_ribbons
+            .iter()
+            .map(|r| r.chars_vec.iter().collect::<String>().trim_matches(BLANK).to_string())
+            .collect()
+    }
+
+    // the single branch of a deterministic machine ends like its run
+    fn same_as_run(tm: &TuringMachine, input: &str) {
+        let run = execute::run(tm, input, 10000).unwrap();
+        let (_, initial) = TuringMachineExecutor::new(tm.clone(), input.to_string()).unwrap();
+        let tree = explore(tm, &initial, 10000, 100000);
+
+        let last = tree.nodes.last().unwrap();
+        assert_eq!(tree.accepting.is_some(), run.outcome == Outcome::Accepted, "on '{}'", input);
+        assert!(!tree.truncated, "on '{}'", input);
+        assert_eq!(last.depth, run.steps, "on '{}'", input);
+        assert_eq!(output(&last.step), output(&run.last), "on '{}'", input);
+    }
+
+    #[test]
+    fn deterministic_examples_explore_like_they_run() {
+        for example in EXAMPLES.iter() {
+            let tm = source::parse(&compose::expand(example.code).unwrap().code).unwrap();
+            let alphabet: Vec<char> = analysis::ribbon_symbols(&tm, 0).into_iter().filter(|c| *c != BLANK).collect();
+            for input in execute::words(&alphabet, 4) {
+                same_as_run(&tm, &input);
+            }
+        }
+    }
+
+    #[test]
+    fn accepting_states_with_rules_accept_when_halting() {
+        // a is accepting but goes on reading the x
+        let tm = source::parse("accepting: a;\n\ns {x, _ -> R, _, N} a;\na {x, _ -> R, _, N} s;").unwrap();
+        for input in ["", "x", "xx", "xxx"] {
+            same_as_run(&tm, input);
+        }
+    }
+
+    #[test]
+    fn left_moves_at_the_start_grow_the_ribbons() {
+        let tm = source::parse(
+            "accepting: done;\n\nstart {a, _ -> L, b, L} back;\nback {_, _ -> R, c, R} done;",
+        )
+        .unwrap();
+        same_as_run(&tm, "a");
+    }
+}
@@ -0,0 +1,336 @@
+use itertools::Itertools;
+use rand::random_range;
+use turingrs::turing_machine::TuringMachine;
+
+use crate::execute::{self, Outcome};
+
+/// How the inputs of a profile are generated for a size n
+#[derive(Clone, PartialEq, Debug)]
+pub enum Inputs {
+    /// Symbols and groups repeated with `^`: `a^n b^n`, `(ab)^2n`, `0^3 1^n`
+    Pattern(String),
+    /// Words of length n drawn at random over an alphabet, several per length
+    Random { alphabet: String, samples: usize },
+}
+
+/// A run of the profile
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub input: String,
+    pub outcome: Outcome,
+    pub steps: usize,
+    pub space: usize,
+}
+
+impl Sample {
+    pub fn length(&self) -> usize {
+        self.input.chars().count()
+    }
+}
+
+/// A profile going on, advanced a slice at a time to keep the interface responsive
+pub struct Profiler {
+    tm: TuringMachine,
+    inputs: Inputs,
+    max: usize,
+    budget: usize,
+    /// Next size to generate inputs for
+    n: usize,
+    /// Inputs of the current size left to run
+    pending: Vec<String>,
+    done: bool,
+    pub samples: Vec<Sample>,
+}
+
+impl Profiler {
+    /// Run a machine on inputs of size 0 to max, each under a budget of steps
+    pub fn new(tm: &TuringMachine, inputs: &Inputs, max: usize, budget: usize) -> Result<Self, String> {
+        match inputs {
+            Inputs::Pattern(pattern) => {
+                pattern_input(pattern, 0)?;
+            }
+            Inputs::Random { alphabet, .. } => {
+                if alphabet.chars().all(char::is_whitespace) {
+                    return Err(String::from("The alphabet is empty"));
+                }
+            }
+        }
+        Ok(Profiler {
+            tm: tm.clone(),
+            inputs: inputs.clone(),
+            max,
+            budget,
+            n: 0,
+            pending: vec![],
+            done: false,
+            samples: vec![],
+        })
+    }
+
+    /// Whether every size was run or the profile stopped
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    /// Stop the profile, the runs so far being kept
+    pub fn stop(&mut self) {
+        self.done = true;
+    }
+
+    /// Run inputs until about `work` steps were run, returning whether the profile is over
+    pub fn advance(&mut self, work: usize) -> Result<bool, String> {
+        let mut spent = 0;
+        while !self.done && spent < work {
+            let Some(input) = self.pending.pop() else {
+                if self.n > self.max {
+                    self.done = true;
+                } else {
+                    self.pending = self.words(self.n)?;
+                    self.n += 1;
+                }
+                continue;
+            };
+
+            let run = execute::run(&self.tm, &input, self.budget)?;
+            spent += run.steps.max(1);
+            self.samples.push(Sample { outcome: run.outcome, steps: run.steps, space: run.space(), input });
+        }
+        Ok(self.done)
+    }
+
+    // inputs of size n, in reverse order of running
+    fn words(&self, n: usize) -> Result<Vec<String>, String> {
+        match &self.inputs {
+            Inputs::Pattern(pattern) => Ok(vec![pattern_input(pattern, n)?]),
+            Inputs::Random { alphabet, samples } => {
+                let alphabet: Vec<char> = alphabet.chars().filter(|c| !c.is_whitespace()).collect();
+                Ok((0..*samples).map(|_| random_input(&alphabet, n)).collect())
+            }
+        }
+    }
+}
+
+/// Input of size n described by a pattern
+pub fn pattern_input(pattern: &str, n: usize) -> Result<String, String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut pos = 0;
+    let input = expand(&chars, &mut pos, n)?;
+    if pos < chars.len() {
+        return Err(format!("Unexpected '{}' in the pattern", chars[pos]));
+    }
+    Ok(input)
+}
+
+// expand a pattern up to the end of its group
+fn expand(chars: &[char], pos: &mut usize, n: usize) -> Result<String, String> {
+    let mut input = String::new();
+
+    while let Some(c) = chars.get(*pos) {
+        let unit = match c {
+            ')' => break,
+            c if c.is_whitespace() => {
+                *pos += 1;
+                continue;
+            }
+            '(' => {
+                *pos += 1;
+                let group = expand(chars, pos, n)?;
+                if chars.get(*pos) != Some(&')') {
+                    return Err(String::from("Missing ')' in the pattern"));
+                }
+                *pos += 1;
+                group
+            }
+            c => {
+                *pos += 1;
+                c.to_string()
+            }
+        };
+
+        let times = if chars.get(*pos) == Some(&'^') {
+            *pos += 1;
+            repetitions(chars, pos, n)?
+        } else {
+            1
+        };
+        input += &unit.repeat(times);
+    }
+    Ok(input)
+}
+
+// number of repetitions after a '^': a number, n, or a number times n
+fn repetitions(chars: &[char], pos: &mut usize, n: usize) -> Result<usize, String> {
+    let digits: String = chars[*pos..].iter().take_while(|c| c.is_ascii_digit()).collect();
+    *pos += digits.len();
+    let factor = if digits.is_empty() { None } else { digits.parse::<usize>().ok() };
+
+    match (factor, chars.get(*pos)) {
+        (factor, Some('n')) => {
+            *pos += 1;
+            Ok(factor.unwrap_or(1) * n)
+        }
+        (Some(factor), _) => Ok(factor),
+        (None, _) => Err(String::from("Expected a number or n after '^' in the pattern")),
+    }
+}
+
+/// Word of a length drawn at random over an alphabet
+pub fn random_input(alphabet: &[char], length: usize) -> String {
+    (0..length).map(|_| alphabet[random_range(0..alphabet.len())]).collect()
+}
+
+/// Growth of a cost against the input length
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Growth {
+    Linear,
+    NLogN,
+    Quadratic,
+    Exponential,
+}
+
+impl Growth {
+    pub const ALL: [Growth; 4] = [Growth::Linear, Growth::NLogN, Growth::Quadratic, Growth::Exponential];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Growth::Linear => "n",
+            Growth::NLogN => "n log n",
+            Growth::Quadratic => "n²",
+            Growth::Exponential => "2ⁿ",
+        }
+    }
+
+    pub fn apply(&self, n: f64) -> f64 {
+        match self {
+            Growth::Linear => n,
+            Growth::NLogN => if n > 1.0 { n * n.log2() } else { 0.0 },
+            Growth::Quadratic => n * n,
+            Growth::Exponential => n.min(1000.0).exp2(),
+        }
+    }
+}
+
+/// A cost fitted as a * growth(n) + b
+#[derive(Clone, Copy, Debug)]
+pub struct Fit {
+    pub growth: Growth,
+    pub a: f64,
+    pub b: f64,
+    /// Sum of the squared errors
+    pub error: f64,
+}
+
+impl Fit {
+    pub fn apply(&self, n: f64) -> f64 {
+        self.a * self.growth.apply(n) + self.b
+    }
+}
+
+impl std::fmt::Display for Fit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.3} · {} {:+.1}", self.a, self.growth.name(), self.b)
+    }
+}
+
+/// Growth fitting the best (length, cost) points with least squares, the simplest winning ties
+pub fn fit(points: &[(f64, f64)]) -> Option<Fit> {
+    Growth::ALL
+        .into_iter()
+        .filter_map(|growth| fit_growth(points, growth))
+        .fold(None, |best: Option<Fit>, fit| match best {
+            Some(best) if best.error <= fit.error => Some(best),
+            _ => Some(fit),
+        })
+}
+
+fn fit_growth(points: &[(f64, f64)], growth: Growth) -> Option<Fit> {
+    let count = points.len() as f64;
+    let xs: Vec<f64> = points.iter().map(|(n, _)| growth.apply(*n)).collect();
+    let mean_x = xs.iter().sum::<f64>() / count;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+
+    let variance: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+    if !variance.is_finite() || variance == 0.0 {
+        return None;
+    }
+    let covariance: f64 = xs.iter().zip(points.iter()).map(|(x, (_, y))| (x - mean_x) * (y - mean_y)).sum();
+
+    let a = covariance / variance;
+    let b = mean_y - a * mean_x;
+    let error = xs.iter().zip(points.iter()).map(|(x, (_, y))| (a * x + b - y).powi(2)).sum();
+    Some(Fit { growth, a, b, error })
+}
+
+/// Worst cost for every input length, the runs out of budget being left out
+pub fn worst_case(samples: &[Sample], cost: impl Fn(&Sample) -> usize) -> Vec<(f64, f64)> {
+    samples
+        .iter()
+        .filter(|s| s.outcome != Outcome::Timeout)
+        .into_group_map_by(|s| s.length())
+        .into_iter()
+        .map(|(length, samples)| (length as f64, samples.into_iter().map(&cost).max().unwrap_or(0) as f64))
+        .sorted_by(|a, b| a.0.total_cmp(&b.0))
+        .collect()
+}
+
+/// The samples as CSV, one line per run
+pub fn to_csv(samples: &[Sample]) -> String {
+    let mut lines = vec![String::from("length,input,outcome,steps,space")];
+    for s in samples {
+        lines.push(format!(
+            "{},\"{}\",{},{},{}",
+            s.length(),
+            s.input.replace('"', "\"\""),
+            s.outcome.label(),
+            s.steps,
+            s.space
+        ));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_repeat_symbols_and_groups() {
+        assert_eq!(pattern_input("a^n b^n", 3).unwrap(), "aaabbb");
+        assert_eq!(pattern_input("a^n b^n", 0).unwrap(), "");
+        assert_eq!(pattern_input("(ab)^2n", 2).unwrap(), "abababab");
+        assert_eq!(pattern_input("0^3 1^n", 2).unwrap(), "00011");
+        assert_eq!(pattern_input("((a)^2 b)^n c", 2).unwrap(), "aabaabc");
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert!(pattern_input("a^", 1).is_err());
+        assert!(pattern_input("a^x", 1).is_err());
+        assert!(pattern_input("(ab", 1).is_err());
+        assert!(pattern_input("ab)", 1).is_err());
+    }
+
+    #[test]
+    fn repetitions_of_n_and_numbers() {
+        let count = |text: &str| repetitions(&text.chars().collect::<Vec<char>>(), &mut 0, 5);
+        assert_eq!(count("n"), Ok(5));
+        assert_eq!(count("3n"), Ok(15));
+        assert_eq!(count("4"), Ok(4));
+        assert!(count("").is_err());
+    }
+
+    #[test]
+    fn exact_data_fits_its_growth() {
+        let points = |f: fn(f64) -> f64| (0..20).map(|n| (n as f64, f(n as f64))).collect::<Vec<_>>();
+
+        let linear = fit(&points(|n| 3.0 * n + 2.0)).unwrap();
+        assert_eq!(linear.growth, Growth::Linear);
+        assert!((linear.a - 3.0).abs() < 1e-9 && (linear.b - 2.0).abs() < 1e-9);
+
+        let quadratic = fit(&points(|n| n * n + 1.0)).unwrap();
+        assert_eq!(quadratic.growth, Growth::Quadratic);
+        assert!((quadratic.a - 1.0).abs() < 1e-9);
+
+        assert!(fit(&[(1.0, 1.0)]).is_none());
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use turingrs::{
+    parser::parse_turing_machine,
+    turing_machine::TuringMachine,
+    turing_state::{TuringDirection, TuringTransition},
+};
+
+/// Keyword of the line declaring the accepting states
+pub const ACCEPTING: &str = "accepting";
+
+/// Symbol of an empty cell
+pub const BLANK: char = '_';
+
+/// Comment naming the ribbons, read ribbon first: `// tapes: input, work, output`
+pub const TAPES: &str = "tapes";
+
+/// Comment saving a test of the machine: `// test: "abba" accepts` or `// test: "ab" refuses`
+pub const TEST: &str = "test";
+
+/// Chars separating the parts of a rule, that can not be read or written
+pub const SEPARATORS: [char; 8] = [',', ';', ':', '{', '}', '-', '>', '/'];
+
+/// Whether a char can be a symbol of a rule
+pub fn is_symbol(c: char) -> bool {
+    !c.is_whitespace() && !SEPARATORS.contains(&c)
+}
+
+// convert a direction to its code representation
+pub fn direction_to_str(direction: &TuringDirection) -> &'static str {
+    match direction {
+        TuringDirection::Left => "L",
+        TuringDirection::Right => "R",
+        TuringDirection::None => "N",
+    }
+}
+
+// return the name of the state at the given index
+pub fn state_name(tm: &TuringMachine, index: u8) -> String {
+    tm.name_index_hashmap
+        .iter()
+        .find(|(_, i)| **i == index)
+        .map(|(name, _)| name.clone())
+        .unwrap_or_else(|| index.to_string())
+}
+
+/// Body of a rule, what is written between the braces: the symbols read on every ribbon,
+/// the move of the read ribbon, then a (symbol, move) pair for every write ribbon
+pub fn rule_body(transition: &TuringTransition) -> String {
+    format_rule(
+        &transition.chars_read,
+        &transition.move_read,
+        &transition.chars_write,
+    )
+}
+
+// build the body of a rule from its parts
+pub fn format_rule(
+    chars_read: &[char],
+    move_read: &TuringDirection,
+    chars_write: &[(char, TuringDirection)],
+) -> String {
+    let mut parts = vec![format!(
+        "{} -> {}",
+        chars_read.iter().join(", "),
+        direction_to_str(move_read)
+    )];
+    for (c, d) in chars_write.iter() {
+        parts.push(format!("{}, {}", c, direction_to_str(d)));
+    }
+    parts.join(", ")
+}
+
+// build a full rule line of the code
+pub fn rule_line(from: &str, body: &str, to: &str) -> String {
+    format!("{} {{{}}} {};", from, body, to)
+}
+
+/// Lines of the code defining the states and their rules
+#[derive(Default)]
+pub struct Spans {
+    /// First and last line of the rules of every state
+    pub states: HashMap<u8, (usize, usize)>,
+    /// Line of every rule, by state and index of the transition in the state
+    pub rules: HashMap<(u8, usize), usize>,
+}
+
+/// Find the lines of the code defining the rules of a machine compiled from it.
+///
+/// A rule is written back and looked for among the lines, spacing and trailing comments
+/// aside. Rules split over several lines or with their parts laid out differently get no
+/// line, and the lines found no longer hold once the code is edited.
+pub fn spans(code: &str, tm: &TuringMachine) -> Spans {
+    let mut lines: HashMap<String, usize> = HashMap::new();
+    for (i, line) in code.lines().enumerate() {
+        lines.entry(compact(line)).or_insert(i);
+    }
+
+    let mut spans = Spans::default();
+    for index in 0..tm.states.len() as u8 {
+        let from = state_name(tm, index);
+        for (id, t) in tm.get_state(index).transitions.iter().enumerate() {
+            let rule = rule_line(&from, &rule_body(t), &state_name(tm, t.index_to_state));
+            let Some(line) = lines.get(&compact(&rule)).copied() else {
+                continue;
+            };
+            spans.rules.insert((index, id), line);
+            spans
+                .states
+                .entry(index)
+                .and_modify(|(first, last)| {
+                    *first = (*first).min(line);
+                    *last = (*last).max(line);
+                })
+                .or_insert((line, line));
+        }
+    }
+    spans
+}
+
+// a line without its spacing and trailing comment
+fn compact(line: &str) -> String {
+    line.split("//")
+        .next()
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect()
+}
+
+/// Serialize a turing machine to code that can be compiled back to the same machine.
+///
+/// The initial state comes first, then states are written in index order.
+pub fn to_source(tm: &TuringMachine) -> String {
+    let mut lines = vec![];
+
+    let finals: Vec<String> = (0..tm.states.len() as u8)
+        .filter(|i| tm.get_state(*i).is_final)
+        .map(|i| state_name(tm, i))
+        .collect();
+    if !finals.is_empty() {
+        lines.push(format!("{}: {};", ACCEPTING, finals.join(", ")));
+        lines.push(String::new());
+    }
+
+    for index in 0..tm.states.len() as u8 {
+        let from = state_name(tm, index);
+        for t in tm.get_state(index).transitions.iter() {
+            lines.push(rule_line(&from, &rule_body(t), &state_name(tm, t.index_to_state)));
+        }
+    }
+
+    lines.join("\n")
+}
+
+// names given to the ribbons in the code, empty if none
+pub fn tape_names(code: &str) -> Vec<String> {
+    code.lines()
+        .filter_map(|l| l.trim().strip_prefix("//"))
+        .find_map(|l| l.trim().strip_prefix(TAPES)?.trim_start().strip_prefix(':'))
+        .map(|names| {
+            names
+                .trim()
+                .trim_end_matches(';')
+                .split(',')
+                .map(|n| n.trim().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// comment line naming the ribbons
+pub fn tapes_comment(names: &[String]) -> String {
+    format!("// {}: {}", TAPES, names.join(", "))
+}
+
+// tests saved in the comments of a code, with whether the input is accepted
+pub fn tests(code: &str) -> Vec<(String, bool)> {
+    code.lines()
+        .filter_map(|l| l.trim().strip_prefix("//"))
+        .filter_map(|l| l.trim().strip_prefix(TEST)?.trim_start().strip_prefix(':'))
+        .filter_map(|test| {
+            let (input, verdict) = test.trim().strip_prefix('"')?.rsplit_once('"')?;
+            match verdict.trim().trim_end_matches(';') {
+                "accepts" => Some((input.to_string(), true)),
+                "refuses" => Some((input.to_string(), false)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+// comment line saving a test
+pub fn test_comment(input: &str, accepted: bool) -> String {
+    format!("// {}: \"{}\" {}", TEST, input, if accepted { "accepts" } else { "refuses" })
+}
+
+// compile a code, returning a readable error on failure
+pub fn parse(code: &str) -> Result<TuringMachine, String> {
+    parse_turing_machine(code.to_string()).map_err(|e| format!("{:?}", e))
+}
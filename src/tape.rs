@@ -0,0 +1,310 @@
+use turingrs::{turing_machine::TuringExecutionStep, turing_state::TuringDirection};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::source::BLANK;
+
+/// A ribbon as displayed, one grapheme per cell so that symbols made of several code points
+/// (combining characters, emoji sequences) take a single cell
+#[derive(Debug, PartialEq)]
+pub struct Cells {
+    pub symbols: Vec<String>,
+    pub head: i32,
+}
+
+impl Cells {
+    /// Symbol of a cell, empty outside of the ribbon
+    pub fn get(&self, cell: i32) -> &str {
+        usize::try_from(cell)
+            .ok()
+            .and_then(|c| self.symbols.get(c))
+            .map(String::as_str)
+            .unwrap_or("")
+    }
+
+    /// First and last cells that are not blank
+    pub fn written_bounds(&self) -> Option<(i32, i32)> {
+        let first = self.symbols.iter().position(|s| !is_blank(s))?;
+        let last = self.symbols.iter().rposition(|s| !is_blank(s))?;
+        Some((first as i32, last as i32))
+    }
+}
+
+// whether a cell holds the blank symbol or is outside of the ribbon
+pub fn is_blank(symbol: &str) -> bool {
+    let mut chars = symbol.chars();
+    match (chars.next(), chars.next()) {
+        (None, _) => true,
+        (Some(c), None) => c == BLANK,
+        _ => false,
+    }
+}
+
+// content and head of a ribbon of a step, 0 being the read ribbon
+pub fn ribbon(step: &TuringExecutionStep, index: usize) -> (&[char], usize) {
+    if index == 0 {
+        (&step.read_ribbon.chars_vec, step.read_ribbon.pointer)
+    } else {
+        let r = &step.write_ribbons[index - 1];
+        (&r.chars_vec, r.pointer)
+    }
+}
+
+// mutable content and head of a ribbon of a step, 0 being the read ribbon
+pub fn ribbon_mut(step: &mut TuringExecutionStep, index: usize) -> (&mut Vec<char>, &mut usize) {
+    if index == 0 {
+        let r = &mut step.read_ribbon;
+        (&mut r.chars_vec, &mut r.pointer)
+    } else {
+        let r = &mut step.write_ribbons[index - 1];
+        (&mut r.chars_vec, &mut r.pointer)
+    }
+}
+
+/// Chars under the heads of every ribbon of a step, as read by the machine
+pub fn read(step: &TuringExecutionStep) -> Vec<char> {
+    (0..=step.write_ribbons.len())
+        .map(|i| {
+            let (chars, pointer) = ribbon(step, i);
+            chars.get(pointer).copied().unwrap_or(BLANK)
+        })
+        .collect()
+}
+
+/// Move a head by one char, growing the ribbon with a blank before its start
+pub fn shift(chars: &mut Vec<char>, pointer: &mut usize, direction: &TuringDirection) {
+    match direction {
+        TuringDirection::Left if *pointer == 0 => chars.insert(0, BLANK),
+        TuringDirection::Left => *pointer -= 1,
+        TuringDirection::Right => *pointer += 1,
+        TuringDirection::None => (),
+    }
+}
+
+/// Cells added before the start of a ribbon by a step, its head moving left of the first cell.
+/// The blanks added this way take a cell each.
+pub fn grown_left(before: &TuringExecutionStep, after: &TuringExecutionStep, index: usize) -> usize {
+    let direction = if index == 0 {
+        &after.transition_taken.move_read
+    } else {
+        match after.transition_taken.chars_write.get(index - 1) {
+            Some((_, direction)) => direction,
+            None => return 0,
+        }
+    };
+    let (_, pointer) = ribbon(before, index);
+    usize::from(pointer == 0 && matches!(direction, TuringDirection::Left))
+}
+
+/// Split a ribbon in cells. The head is the cell containing the char under the pointer,
+/// past the end every char is a cell.
+pub fn cells(chars: &[char], pointer: usize) -> Cells {
+    let text: String = chars.iter().collect();
+    let mut symbols = vec![];
+    let mut head = None;
+    let mut offset = 0;
+
+    for g in text.graphemes(true) {
+        let len = g.chars().count();
+        if head.is_none() && pointer < offset + len {
+            head = Some(symbols.len() as i32);
+        }
+        symbols.push(g.to_string());
+        offset += len;
+    }
+
+    let head = head.unwrap_or((symbols.len() + pointer - offset) as i32);
+    Cells { symbols, head }
+}
+
+/// Write a symbol on a cell of a ribbon, the head staying on the same cell.
+/// Rules read a single char, a symbol of several code points could never be read.
+pub fn write(chars: &mut Vec<char>, pointer: &mut usize, cell: i32, symbol: &str) -> Result<(), String> {
+    let mut symbol_chars = symbol.chars();
+    let (Some(c), None) = (symbol_chars.next(), symbol_chars.next()) else {
+        return Err(format!("'{}' is not a single char, no rule could read it", symbol));
+    };
+
+    let (start, len) = grow(chars, pointer, cell);
+    let head = cells(chars, *pointer).head;
+    chars.splice(start..start + len, [c]);
+
+    *pointer = cell_range(chars, head).0;
+    Ok(())
+}
+
+/// Place the head of a ribbon on a cell
+pub fn move_head(chars: &mut Vec<char>, pointer: &mut usize, cell: i32) {
+    *pointer = grow(chars, pointer, cell).0;
+}
+
+// grow a ribbon with blanks so the cell exists, returning its range of chars
+fn grow(chars: &mut Vec<char>, pointer: &mut usize, cell: i32) -> (usize, usize) {
+    if cell < 0 {
+        let shift = (-cell) as usize;
+        chars.splice(0..0, std::iter::repeat_n(BLANK, shift));
+        *pointer += shift;
+        return (0, 1);
+    }
+
+    let count = cells(chars, *pointer).symbols.len();
+    if cell as usize >= count {
+        chars.extend(std::iter::repeat_n(BLANK, cell as usize + 1 - count));
+    }
+    cell_range(chars, cell)
+}
+
+// range of chars of a cell, past the end every char is a cell
+fn cell_range(chars: &[char], cell: i32) -> (usize, usize) {
+    let text: String = chars.iter().collect();
+    let mut offset = 0;
+    let mut count = 0;
+    for g in text.graphemes(true) {
+        let len = g.chars().count();
+        if count == cell {
+            return (offset, len);
+        }
+        offset += len;
+        count += 1;
+    }
+    (offset + (cell - count).max(0) as usize, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use turingrs::turing_state::TuringTransition;
+
+    use super::*;
+
+    /// e + combining acute accent, woman technologist (ZWJ sequence), flag (regional indicators)
+    const SYMBOLS: [&str; 3] = ["e\u{301}", "\u{1F469}\u{200D}\u{1F4BB}", "\u{1F1EB}\u{1F1F7}"];
+
+    fn step_with(content: &str, pointer: usize) -> TuringExecutionStep {
+        let mut step = TuringExecutionStep::new(2);
+        for index in 0..3 {
+            let (chars, p) = ribbon_mut(&mut step, index);
+            *chars = content.chars().collect();
+            *p = pointer;
+        }
+        step
+    }
+
+    #[test]
+    fn multi_codepoint_symbols_take_one_cell_on_every_ribbon() {
+        let content: String = SYMBOLS.concat();
+        let step = step_with(&content, 0);
+
+        for index in 0..3 {
+            let (chars, pointer) = ribbon(&step, index);
+            let cells = cells(chars, pointer);
+            assert_eq!(cells.symbols, SYMBOLS.to_vec(), "ribbon {}", index);
+            assert_eq!(cells.head, 0, "ribbon {}", index);
+        }
+    }
+
+    #[test]
+    fn head_is_the_cell_containing_the_pointer() {
+        let content: String = SYMBOLS.concat();
+        // the pointer on the ZWJ of the second symbol
+        let step = step_with(&content, 3);
+
+        for index in 0..3 {
+            let (chars, pointer) = ribbon(&step, index);
+            assert_eq!(cells(chars, pointer).head, 1, "ribbon {}", index);
+        }
+    }
+
+    #[test]
+    fn head_past_the_end() {
+        let content: String = SYMBOLS.concat();
+        let len = content.chars().count();
+        let step = step_with(&content, len + 2);
+
+        for index in 0..3 {
+            let (chars, pointer) = ribbon(&step, index);
+            let cells = cells(chars, pointer);
+            assert_eq!(cells.head, 5, "ribbon {}", index);
+            assert_eq!(cells.get(5), "");
+            assert!(is_blank(cells.get(5)));
+        }
+    }
+
+    #[test]
+    fn write_around_multi_codepoint_symbols_on_every_ribbon() {
+        let content = format!("{}b", SYMBOLS[1]);
+        let mut step = step_with(&content, 3);
+
+        for index in 0..3 {
+            let (chars, pointer) = ribbon_mut(&mut step, index);
+            write(chars, pointer, 0, "a").unwrap();
+            write(chars, pointer, 3, "c").unwrap();
+
+            let cells = cells(chars, *pointer);
+            assert_eq!(cells.symbols, vec!["a", "b", "_", "c"], "ribbon {}", index);
+            // the head stays on "b"
+            assert_eq!(cells.head, 1, "ribbon {}", index);
+        }
+    }
+
+    #[test]
+    fn multi_codepoint_symbols_are_not_written() {
+        let mut step = step_with("ab", 1);
+
+        for index in 0..3 {
+            let (chars, pointer) = ribbon_mut(&mut step, index);
+            assert!(write(chars, pointer, 0, SYMBOLS[0]).is_err());
+            assert!(write(chars, pointer, 0, "").is_err());
+            assert_eq!(cells(chars, *pointer).symbols, vec!["a", "b"], "ribbon {}", index);
+        }
+    }
+
+    #[test]
+    fn write_before_the_start_shifts_the_head() {
+        let mut step = step_with(SYMBOLS[2], 0);
+
+        for index in 0..3 {
+            let (chars, pointer) = ribbon_mut(&mut step, index);
+            write(chars, pointer, -1, "a").unwrap();
+
+            let cells = cells(chars, *pointer);
+            assert_eq!(cells.symbols, vec!["a", SYMBOLS[2]], "ribbon {}", index);
+            assert_eq!(cells.head, 1, "ribbon {}", index);
+            assert_eq!(cells.written_bounds(), Some((0, 1)));
+        }
+    }
+
+    #[test]
+    fn ribbons_grow_left_when_the_head_leaves_the_first_cell() {
+        let before = step_with("ab", 0);
+        let mut after = step_with("_ab", 0);
+        after.transition_taken = TuringTransition::new(
+            vec!['a', 'a'],
+            TuringDirection::Left,
+            vec![('a', TuringDirection::None), ('a', TuringDirection::Right)],
+        );
+
+        assert_eq!(grown_left(&before, &after, 0), 1);
+        assert_eq!(grown_left(&before, &after, 1), 0);
+        assert_eq!(grown_left(&before, &after, 2), 0);
+        assert_eq!(grown_left(&step_with("ab", 1), &after, 0), 0);
+    }
+
+    #[test]
+    fn move_head_on_multi_codepoint_symbols() {
+        let content: String = SYMBOLS.concat();
+        let mut step = step_with(&content, 0);
+
+        for index in 0..3 {
+            let (chars, pointer) = ribbon_mut(&mut step, index);
+            move_head(chars, pointer, 2);
+            assert_eq!(cells(chars, *pointer).head, 2, "ribbon {}", index);
+        }
+    }
+
+    #[test]
+    fn blank_symbols() {
+        assert!(is_blank(""));
+        assert!(is_blank("_"));
+        assert!(!is_blank(" "));
+        assert!(!is_blank(SYMBOLS[0]));
+    }
+}
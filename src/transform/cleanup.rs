@@ -0,0 +1,272 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use turingrs::turing_machine::TuringMachine;
+
+use crate::{
+    analysis,
+    source::{self, ACCEPTING},
+};
+
+/// A transformation tidying a machine without changing what it does
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Cleanup {
+    /// Remove the states the initial state can not reach
+    Unreachable,
+    /// Merge the states behaving the same, until no more can be merged
+    Merge,
+    /// Name the states q0, q1... in the order they are reached
+    Rename,
+    /// Sort the rules of every state by the symbols they read, keeping the order of the ones reading the same
+    Sort,
+}
+
+impl Cleanup {
+    pub const ALL: [Cleanup; 4] = [Cleanup::Unreachable, Cleanup::Merge, Cleanup::Rename, Cleanup::Sort];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Cleanup::Unreachable => "Remove unreachable states",
+            Cleanup::Merge => "Merge equivalent states",
+            Cleanup::Rename => "Rename states canonically",
+            Cleanup::Sort => "Sort rules",
+        }
+    }
+
+    /// Code of the cleaned machine
+    pub fn apply(&self, tm: &TuringMachine) -> Result<Cleaned, String> {
+        if tm.states.is_empty() {
+            return Err(String::from("The machine has no state"));
+        }
+        let all: Vec<u8> = (0..tm.states.len() as u8).collect();
+        let name = |i: u8| source::state_name(tm, i);
+
+        let cleaned = match self {
+            Cleanup::Unreachable => {
+                let reachable = analysis::reachable(tm);
+                let kept: Vec<u8> = all.into_iter().filter(|i| reachable.contains(i)).collect();
+                let summary = format!("{} unreachable states removed", tm.states.len() - kept.len());
+                rebuild(tm, &kept, |i| i, name, false, summary)
+            }
+            Cleanup::Merge => {
+                let representative = equivalent_states(tm);
+                let kept: Vec<u8> = all.into_iter().filter(|i| representative[*i as usize] == *i).collect();
+                let summary = format!("{} states merged", tm.states.len() - kept.len());
+                rebuild(tm, &kept, |i| representative[i as usize], name, false, summary)
+            }
+            Cleanup::Rename => {
+                let order = reach_order(tm);
+                let numbers: HashMap<u8, usize> = order.iter().enumerate().map(|(n, i)| (*i, n)).collect();
+                let summary = format!("{} states renamed", order.len());
+                rebuild(tm, &order, |i| i, |i| format!("q{}", numbers[&i]), false, summary)
+            }
+            Cleanup::Sort => rebuild(tm, &all, |i| i, name, true, String::from("Rules sorted")),
+        };
+        source::parse(&cleaned.code)?;
+        Ok(cleaned)
+    }
+}
+
+/// Code of a cleaned machine
+pub struct Cleaned {
+    pub code: String,
+    /// State of the original machine each state of the cleaned one comes from, by name
+    pub origins: HashMap<String, u8>,
+    pub summary: String,
+}
+
+// code keeping some states in order, every rule going to the state kept for its target
+fn rebuild(
+    tm: &TuringMachine,
+    kept: &[u8],
+    target: impl Fn(u8) -> u8,
+    name: impl Fn(u8) -> String,
+    sort: bool,
+    summary: String,
+) -> Cleaned {
+    let mut lines = vec![];
+
+    let finals: Vec<String> = kept.iter().filter(|i| tm.get_state(**i).is_final).map(|i| name(*i)).collect();
+    if !finals.is_empty() {
+        lines.push(format!("{}: {};", ACCEPTING, finals.join(", ")));
+        lines.push(String::new());
+    }
+
+    for index in kept.iter() {
+        let mut transitions: Vec<_> = tm.get_state(*index).transitions.iter().collect();
+        if sort {
+            // stable, the rules reading the same symbols keep their order for nondeterministic machines
+            transitions.sort_by_key(|t| t.chars_read.clone());
+        }
+        for t in transitions {
+            lines.push(source::rule_line(&name(*index), &source::rule_body(t), &name(target(t.index_to_state))));
+        }
+    }
+
+    Cleaned {
+        code: lines.join("\n"),
+        origins: kept.iter().map(|i| (name(*i), *i)).collect(),
+        summary,
+    }
+}
+
+// state standing for every state: the first one of the states behaving the same.
+// States are split by whether they accept and by their rules in order, the targets being
+// compared by group, until the groups do not change.
+fn equivalent_states(tm: &TuringMachine) -> Vec<u8> {
+    let count = tm.states.len();
+    let mut group = vec![0usize; count];
+
+    loop {
+        let mut numbers: HashMap<(bool, Vec<(String, usize)>), usize> = HashMap::new();
+        let refined: Vec<usize> = (0..count as u8)
+            .map(|i| {
+                let state = tm.get_state(i);
+                let rules = state
+                    .transitions
+                    .iter()
+                    .map(|t| (source::rule_body(t), group[t.index_to_state as usize]))
+                    .collect();
+                let next = numbers.len();
+                *numbers.entry((state.is_final, rules)).or_insert(next)
+            })
+            .collect();
+
+        let stable = numbers.len() == group.iter().collect::<HashSet<_>>().len();
+        group = refined;
+        if stable {
+            break;
+        }
+    }
+
+    let mut first: HashMap<usize, u8> = HashMap::new();
+    (0..count as u8).map(|i| *first.entry(group[i as usize]).or_insert(i)).collect()
+}
+
+// states in the order they are reached from the initial state, the unreachable ones last
+fn reach_order(tm: &TuringMachine) -> Vec<u8> {
+    let mut order = vec![0u8];
+    let mut seen = HashSet::from([0u8]);
+    let mut queue = VecDeque::from([0u8]);
+    while let Some(index) = queue.pop_front() {
+        for t in tm.get_state(index).transitions.iter() {
+            if seen.insert(t.index_to_state) {
+                order.push(t.index_to_state);
+                queue.push_back(t.index_to_state);
+            }
+        }
+    }
+    order.extend((0..tm.states.len() as u8).filter(|i| !seen.contains(i)));
+    order
+}
+
+/// A line of a diff between two codes
+#[derive(Clone, PartialEq, Debug)]
+pub enum Change {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Lines removed from a code and added to it to get another one, along a longest common subsequence
+pub fn diff(old: &str, new: &str) -> Vec<Change> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j]: length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.push(Change::Same(old[i].to_string()));
+            (i, j) = (i + 1, j + 1);
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] > common[i + 1][j]) {
+            changes.push(Change::Added(new[j].to_string()));
+            j += 1;
+        } else {
+            changes.push(Change::Removed(old[i].to_string()));
+            i += 1;
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execute;
+
+    // two copies of a state counting a pair of a, and a state never reached
+    const REDUNDANT: &str = "accepting: done;\n\n\
+        even {a, _ -> R, _, N} odd;\neven {_, _ -> N, _, N} done;\n\
+        odd {a, _ -> R, _, N} again;\nagain {a, _ -> R, _, N} odd;\n\
+        again {_, _ -> N, _, N} done;\n\
+        lost {a, _ -> R, _, N} even;";
+
+    fn cleaned(cleanup: Cleanup, code: &str) -> TuringMachine {
+        source::parse(&cleanup.apply(&source::parse(code).unwrap()).unwrap().code).unwrap()
+    }
+
+    fn same_outcomes(a: &TuringMachine, b: &TuringMachine) {
+        for input in execute::words(&['a'], 6) {
+            let expected = execute::run(a, &input, 100).unwrap().outcome;
+            assert_eq!(execute::run(b, &input, 100).unwrap().outcome, expected, "on '{}'", input);
+        }
+    }
+
+    #[test]
+    fn cleanups_keep_the_behaviour() {
+        let tm = source::parse(REDUNDANT).unwrap();
+        for cleanup in Cleanup::ALL {
+            same_outcomes(&tm, &cleaned(cleanup, REDUNDANT));
+        }
+    }
+
+    #[test]
+    fn cleanups_remove_and_merge_states() {
+        assert!(!cleaned(Cleanup::Unreachable, REDUNDANT).name_index_hashmap.contains_key("lost"));
+
+        // even and again behave the same, then odd and lost going to them
+        let merged = cleaned(Cleanup::Merge, REDUNDANT);
+        assert!(!merged.name_index_hashmap.contains_key("again"));
+        assert!(!merged.name_index_hashmap.contains_key("lost"));
+        assert_eq!(merged.states.len(), 3);
+
+        let renamed = cleaned(Cleanup::Rename, REDUNDANT);
+        let mut names: Vec<&String> = renamed.name_index_hashmap.keys().collect();
+        names.sort();
+        assert_eq!(names, ["q0", "q1", "q2", "q3", "q4"]);
+    }
+
+    #[test]
+    fn sort_keeps_the_order_of_rules_reading_the_same_symbols() {
+        let code = "accepting: x;\n\ns {b, _ -> R, _, N} s;\ns {a, _ -> R, _, N} y;\ns {a, _ -> R, _, N} x;";
+        let sorted = Cleanup::Sort.apply(&source::parse(code).unwrap()).unwrap().code;
+        let targets: Vec<&str> = sorted.lines().filter(|l| l.starts_with("s ")).map(|l| &l[l.len() - 2..]).collect();
+        assert_eq!(targets, ["y;", "x;", "s;"]);
+    }
+
+    #[test]
+    fn diff_keeps_common_lines() {
+        let changes = diff("a\nb\nc", "a\nc\nd");
+        assert_eq!(
+            changes,
+            [
+                Change::Same(String::from("a")),
+                Change::Removed(String::from("b")),
+                Change::Same(String::from("c")),
+                Change::Added(String::from("d")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,437 @@
+use std::collections::{HashMap, VecDeque};
+
+use egui::{Pos2, pos2};
+use itertools::Itertools;
+use turingrs::{turing_machine::TuringMachine, turing_state::TuringDirection};
+
+use crate::{
+    analysis,
+    format::ImportedMachine,
+    source::{self, ACCEPTING, BLANK},
+};
+
+/// Above this number of track combinations, the machine is not transformed
+const MAX_CELLS: usize = 4096;
+/// Above this number of rules, the machine is not transformed
+const MAX_RULES: usize = 200_000;
+/// States are indexed on a byte
+const MAX_STATES: usize = 256;
+/// First char of the symbols encoding the cells, a block of single width symbols
+const FIRST_SYMBOL: u32 = 0x4E00;
+/// Space between two states of the generated layout
+const SPACING: f32 = 150.0;
+
+const INIT_STATE: &str = "init";
+const COPY_STATE: &str = "copy";
+const REWIND_STATE: &str = "rewind";
+
+/// Simulate a machine with write ribbons on a machine with a single write ribbon.
+///
+/// The single tape has a track per ribbon of the machine, every cell holding a symbol and
+/// a head marker per track, encoded as one symbol. A prologue copies the input on the read
+/// track with every head on the first cell. Then every step of the machine is simulated by
+/// sweeping right from the leftmost head to the rightmost one to read the symbols under the
+/// heads, then sweeping back left to write them and move the heads.
+///
+/// The input alphabet is copied on the read track, so that the symbols the machine does not
+/// read stop it as they would stop the original one. Input symbols outside of it are refused.
+pub fn transform(tm: &TuringMachine, input_alphabet: &[char]) -> Result<ImportedMachine, String> {
+    if tm.k == 0 {
+        return Err(String::from("The machine already has a single tape"));
+    }
+    if tm.states.is_empty() {
+        return Err(String::from("The machine has no state"));
+    }
+
+    let tracks = Tracks::new(tm, input_alphabet)?;
+    let mut builder = Builder {
+        tm,
+        tracks,
+        names: HashMap::new(),
+        order: vec![],
+        queue: VecDeque::new(),
+        lines: vec![],
+        accepting: vec![],
+    };
+
+    builder.prologue();
+    while let Some(phase) = builder.queue.pop_front() {
+        builder.rules(&phase);
+        if builder.lines.len() > MAX_RULES {
+            return Err(format!("The single tape machine would have more than {} rules", MAX_RULES));
+        }
+        if builder.names.len() + 3 > MAX_STATES {
+            return Err(format!("The single tape machine would have more than {} states", MAX_STATES));
+        }
+    }
+
+    let mut lines = vec![source::tapes_comment(&[String::from("input"), String::from("tracks")])];
+    if !builder.accepting.is_empty() {
+        lines.push(format!("{}: {};", ACCEPTING, builder.accepting.join(", ")));
+    }
+    lines.push(String::new());
+    lines.extend(builder.lines.iter().cloned());
+
+    // states on a grid, in the order they are created
+    let names: Vec<String> = [INIT_STATE, COPY_STATE, REWIND_STATE]
+        .into_iter()
+        .map(String::from)
+        .chain(builder.order.iter().map(|p| builder.names[p].clone()))
+        .collect();
+    let width = (names.len() as f32).sqrt().ceil().max(1.0) as usize;
+    let positions = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.clone(), pos2((i % width) as f32 * SPACING, (i / width) as f32 * SPACING)))
+        .collect::<HashMap<String, Pos2>>();
+
+    Ok(ImportedMachine {
+        code: lines.join("\n"),
+        input: None,
+        positions,
+        warnings: vec![format!(
+            "Single tape machine simulating {} write ribbons: {} states, {} rules, inputs over {{{}}}",
+            tm.k,
+            names.len(),
+            builder.lines.len(),
+            builder.tracks.symbols[0].iter().filter(|c| **c != BLANK).join(", ")
+        )],
+    })
+}
+
+/// Symbols of every track and encoding of the cells of the single tape
+struct Tracks {
+    symbols: Vec<Vec<char>>,
+}
+
+/// Content of a cell of the single tape: a symbol per track, as an index in its alphabet,
+/// and the tracks whose head is on the cell
+#[derive(Clone, PartialEq, Debug)]
+struct Cell {
+    symbols: Vec<usize>,
+    heads: u32,
+}
+
+impl Tracks {
+    fn new(tm: &TuringMachine, input_alphabet: &[char]) -> Result<Self, String> {
+        let mut symbols: Vec<Vec<char>> = (0..=tm.k as usize)
+            .map(|ribbon| analysis::ribbon_symbols(tm, ribbon))
+            .collect();
+        for c in input_alphabet {
+            if !symbols[0].contains(c) {
+                symbols[0].push(*c);
+            }
+        }
+
+        let count = symbols.iter().map(|s| s.len() * 2).product::<usize>();
+        if count > MAX_CELLS {
+            return Err(format!(
+                "The ribbons can hold {} combinations of symbols and heads, more than the {} supported",
+                count, MAX_CELLS
+            ));
+        }
+        Ok(Tracks { symbols })
+    }
+
+    fn count(&self) -> usize {
+        self.symbols.len()
+    }
+
+    // every head is on a track
+    fn all(&self) -> u32 {
+        (1 << self.count()) - 1
+    }
+
+    // every content a cell can have
+    fn cells(&self) -> Vec<Cell> {
+        let mut cells = vec![Cell { symbols: vec![], heads: 0 }];
+        for track in self.symbols.iter() {
+            cells = cells
+                .into_iter()
+                .flat_map(|c| {
+                    (0..track.len()).map(move |s| {
+                        let mut symbols = c.symbols.clone();
+                        symbols.push(s);
+                        Cell { symbols, heads: 0 }
+                    })
+                })
+                .collect();
+        }
+        cells
+            .into_iter()
+            .flat_map(|c| (0..=self.all()).map(move |heads| Cell { heads, ..c.clone() }))
+            .collect()
+    }
+
+    // symbol of the single tape encoding a cell, an empty cell being the blank
+    fn encode(&self, cell: &Cell) -> char {
+        let mut index = 0;
+        for (track, s) in self.symbols.iter().zip(cell.symbols.iter()) {
+            index = index * track.len() + s;
+        }
+        index = (index << self.count()) | cell.heads as usize;
+
+        if index == 0 {
+            BLANK
+        } else {
+            char::from_u32(FIRST_SYMBOL + index as u32).unwrap_or(BLANK)
+        }
+    }
+
+    // cell holding a symbol on the read track, with the given heads
+    fn input_cell(&self, c: usize, heads: u32) -> Cell {
+        let mut symbols = vec![0; self.count()];
+        symbols[0] = c;
+        Cell { symbols, heads }
+    }
+}
+
+/// A phase of the simulation of a state of the machine
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+enum Phase {
+    /// Sweeping right, with the symbols found under the heads so far
+    Read { state: u8, read: Vec<Option<char>> },
+    /// The state accepts, no rule applies
+    Halt { state: u8 },
+    /// Sweeping left to apply a rule, with the tracks already written and the heads to place
+    /// on the cell reached
+    Write { state: u8, rule: usize, done: u32, left: u32 },
+    /// One cell right to place heads moving right, then back
+    Place { state: u8, rule: usize, done: u32, right: u32, left: u32 },
+    /// Back on the cell written, going on to the left
+    Back { state: u8, rule: usize, done: u32, left: u32 },
+}
+
+struct Builder<'a> {
+    tm: &'a TuringMachine,
+    tracks: Tracks,
+    names: HashMap<Phase, String>,
+    order: Vec<Phase>,
+    queue: VecDeque<Phase>,
+    lines: Vec<String>,
+    accepting: Vec<String>,
+}
+
+impl Builder<'_> {
+    // name of the state of a phase, creating it when first seen.
+    // Names end with a unique number after the last underscore so they can not collide.
+    fn name(&mut self, phase: Phase) -> String {
+        if let Some(name) = self.names.get(&phase) {
+            return name.clone();
+        }
+
+        let (state, kind) = match &phase {
+            Phase::Read { state, .. } => (*state, "read"),
+            Phase::Halt { state } => (*state, "halt"),
+            Phase::Write { state, .. } => (*state, "write"),
+            Phase::Place { state, .. } => (*state, "place"),
+            Phase::Back { state, .. } => (*state, "back"),
+        };
+        let name = format!("{}_{}_{}", source::state_name(self.tm, state), kind, self.names.len());
+
+        if let Phase::Halt { .. } = phase {
+            self.accepting.push(name.clone());
+        }
+        self.names.insert(phase.clone(), name.clone());
+        self.order.push(phase.clone());
+        self.queue.push_back(phase);
+        name
+    }
+
+    // add a rule reading a cell on the single tape, the read ribbon staying on a blank
+    fn rule(&mut self, from: &str, read: char, write: char, direction: TuringDirection, to: &str) {
+        let body = source::format_rule(&[BLANK, read], &TuringDirection::None, &[(write, direction)]);
+        self.lines.push(source::rule_line(from, &body, to));
+    }
+
+    // copy the input on the read track, every head on the first cell, then rewind
+    fn prologue(&mut self) {
+        let first = self.name(Phase::Read { state: 0, read: vec![None; self.tracks.count()] });
+        let all = self.tracks.all();
+        let input = self.tracks.symbols[0].clone();
+
+        for (i, c) in input.iter().enumerate() {
+            let first_cell = self.tracks.encode(&self.tracks.input_cell(i, all));
+            let body = source::format_rule(&[*c, BLANK], &TuringDirection::Right, &[(first_cell, TuringDirection::Right)]);
+            self.lines.push(source::rule_line(INIT_STATE, &body, COPY_STATE));
+
+            if *c != BLANK {
+                let cell = self.tracks.encode(&self.tracks.input_cell(i, 0));
+                let body = source::format_rule(&[*c, BLANK], &TuringDirection::Right, &[(cell, TuringDirection::Right)]);
+                self.lines.push(source::rule_line(COPY_STATE, &body, COPY_STATE));
+            }
+        }
+
+        let body = source::format_rule(&[BLANK, BLANK], &TuringDirection::None, &[(BLANK, TuringDirection::Left)]);
+        self.lines.push(source::rule_line(COPY_STATE, &body, REWIND_STATE));
+
+        for cell in self.tracks.cells() {
+            let c = self.tracks.encode(&cell);
+            if c != BLANK {
+                self.rule(REWIND_STATE, c, c, TuringDirection::Left, REWIND_STATE);
+            }
+        }
+        self.rule(REWIND_STATE, BLANK, BLANK, TuringDirection::None, &first);
+    }
+
+    // rules of a phase, one per content of the cell under the head
+    fn rules(&mut self, phase: &Phase) {
+        let from = self.names[phase].clone();
+
+        for cell in self.tracks.cells() {
+            let Some((write, direction, next)) = self.step(phase, &cell) else {
+                continue;
+            };
+            let to = self.name(next);
+            let (read, write) = (self.tracks.encode(&cell), self.tracks.encode(&write));
+            self.rule(&from, read, write, direction, &to);
+        }
+    }
+
+    // what a phase does on a cell: the new content of the cell, the move and the next phase
+    fn step(&self, phase: &Phase, cell: &Cell) -> Option<(Cell, TuringDirection, Phase)> {
+        let all = self.tracks.all();
+
+        match phase.clone() {
+            Phase::Read { state, mut read } => {
+                for (track, r) in read.iter_mut().enumerate() {
+                    if cell.heads & (1 << track) != 0 {
+                        *r = Some(self.tracks.symbols[track][cell.symbols[track]]);
+                    }
+                }
+
+                // stop as soon as no rule can apply, to keep the number of states low
+                let rule = self.tm.get_state(state).transitions.iter().position(|t| {
+                    t.chars_read.iter().zip(read.iter()).all(|(c, r)| r.is_none_or(|r| r == *c))
+                });
+                match rule {
+                    Some(_) if read.iter().any(Option::is_none) => {
+                        Some((cell.clone(), TuringDirection::Right, Phase::Read { state, read }))
+                    }
+                    Some(rule) => Some((cell.clone(), TuringDirection::None, Phase::Write { state, rule, done: 0, left: 0 })),
+                    None if self.tm.get_state(state).is_final => {
+                        Some((cell.clone(), TuringDirection::None, Phase::Halt { state }))
+                    }
+                    None => None,
+                }
+            }
+
+            Phase::Halt { .. } => None,
+
+            Phase::Write { state, rule, done, left } => {
+                let mut cell = cell.clone();
+                cell.heads |= left;
+                let t = &self.tm.get_state(state).transitions[rule];
+
+                if done == all {
+                    return Some((cell, TuringDirection::None, Phase::Read {
+                        state: t.index_to_state,
+                        read: vec![None; self.tracks.count()],
+                    }));
+                }
+
+                let mut moves_left = 0;
+                let mut moves_right = 0;
+                let todo = cell.heads & !done;
+
+                for track in (0..self.tracks.count()).filter(|t| todo & (1 << t) != 0) {
+                    let direction = if track == 0 {
+                        &t.move_read
+                    } else {
+                        let (c, direction) = &t.chars_write[track - 1];
+                        cell.symbols[track] = self.tracks.symbols[track].iter().position(|s| s == c)?;
+                        direction
+                    };
+                    match direction {
+                        TuringDirection::Left => moves_left |= 1 << track,
+                        TuringDirection::Right => moves_right |= 1 << track,
+                        TuringDirection::None => (),
+                    }
+                }
+                cell.heads &= !(moves_left | moves_right);
+                let done = done | todo;
+
+                if moves_right != 0 {
+                    Some((cell, TuringDirection::Right, Phase::Place { state, rule, done, right: moves_right, left: moves_left }))
+                } else {
+                    Some((cell, TuringDirection::Left, Phase::Write { state, rule, done, left: moves_left }))
+                }
+            }
+
+            Phase::Place { state, rule, done, right, left } => {
+                let mut cell = cell.clone();
+                cell.heads |= right;
+                Some((cell, TuringDirection::Left, Phase::Back { state, rule, done, left }))
+            }
+
+            Phase::Back { state, rule, done, left } => {
+                Some((cell.clone(), TuringDirection::Left, Phase::Write { state, rule, done, left }))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        examples::EXAMPLES,
+        execute::{self, Outcome},
+    };
+
+    const MAX_STEPS: usize = 200_000;
+
+    fn assert_agree(code: &str, alphabet: &[char], length: usize) {
+        let tm = source::parse(code).unwrap();
+        let single = source::parse(&transform(&tm, alphabet).unwrap().code).unwrap();
+        assert_eq!(single.k, 1);
+
+        for input in execute::words(alphabet, length) {
+            let expected = execute::run(&tm, &input, MAX_STEPS).unwrap().outcome;
+            let outcome = execute::run(&single, &input, MAX_STEPS).unwrap().outcome;
+            assert_ne!(expected, Outcome::Timeout, "input '{}'", input);
+            assert_eq!(outcome, expected, "input '{}'", input);
+        }
+    }
+
+    fn example(name: &str) -> &'static str {
+        EXAMPLES.iter().find(|e| e.name == name).unwrap().code
+    }
+
+    #[test]
+    fn anbncn_agrees_with_its_single_tape_machine() {
+        assert_agree(example("aⁿbⁿcⁿ"), &['a', 'b', 'c'], 6);
+    }
+
+    #[test]
+    fn palindromes_agree_with_their_single_tape_machine() {
+        assert_agree(example("Palindromes"), &['a', 'b'], 6);
+    }
+
+    #[test]
+    fn machine_moving_heads_both_ways() {
+        // reads the input back and forth while moving the write head left of its start
+        let code = "accepting: done;\n\
+            go {a, _ -> R, a, L} go;\n\
+            go {b, _ -> L, b, R} back;\n\
+            back {a, _ -> R, x, N} skip;\n\
+            skip {b, _ -> R, y, L} go;\n\
+            go {_, _ -> N, _, N} done;";
+        assert_agree(code, &['a', 'b'], 5);
+    }
+
+    #[test]
+    fn input_symbols_the_machine_does_not_read() {
+        // accepts on the first b, whatever comes after it
+        let code = "accepting: done;\n\
+            go {a, _ -> R, a, R} go;\n\
+            go {b, _ -> N, _, N} done;";
+        assert_agree(code, &['a', 'b', 'c'], 4);
+    }
+
+    #[test]
+    fn machine_with_a_single_tape_is_refused() {
+        let tm = source::parse("accepting: q;\nq {a -> R} q;").unwrap();
+        assert!(transform(&tm, &['a']).is_err());
+    }
+}
@@ -6,7 +6,19 @@ mod rubans;
 mod control;
 mod graph;
 mod code;
+mod automaton;
+mod beaver;
 mod button;
+mod cleanup;
+mod equivalence;
+mod file;
+mod fuzz;
+mod transform;
+mod history;
+mod ntm;
+mod profile;
+mod universal;
+mod watch;
 pub mod turing;
 pub mod constant;
 
@@ -49,4 +61,16 @@ pub fn show(app: &mut TuringApp, ctx: &egui::Context) {
             code::ui(app, ui);
         });
     });
+
+    // tool windows
+    history::window(app, ctx);
+    watch::window(app, ctx);
+    ntm::window(app, ctx);
+    profile::window(app, ctx);
+    equivalence::window(app, ctx);
+    fuzz::window(app, ctx);
+    beaver::window(app, ctx);
+    automaton::window(app, ctx);
+    universal::window(app, ctx);
+    cleanup::window(app, ctx);
 }
\ No newline at end of file
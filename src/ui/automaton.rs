@@ -0,0 +1,58 @@
+use egui::{Color32, TextEdit, TextStyle, Window, vec2};
+
+use crate::{TuringApp, automaton::Dfa};
+
+// show the generation of a decider from a regular expression or an automaton table
+pub fn window(app: &mut TuringApp, ctx: &egui::Context) {
+    let mut open = app.show_automaton;
+
+    Window::new("Automaton to machine")
+        .open(&mut open)
+        .default_size(vec2(320.0, 300.0))
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut app.automaton_from_table, false, "Regular expression");
+                ui.radio_value(&mut app.automaton_from_table, true, "Transition table");
+            });
+
+            if app.automaton_from_table {
+                ui.add(
+                    TextEdit::multiline(&mut app.automaton_table)
+                        .font(TextStyle::Monospace)
+                        .hint_text("     a    b\n>q0  q1   q0\n*q1  q1   -")
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(6),
+                )
+                .on_hover_text("Symbols in the header, a line per state: > initial, * accepting, - no transition");
+            } else {
+                ui.add(
+                    TextEdit::singleline(&mut app.automaton_regex)
+                        .font(TextStyle::Monospace)
+                        .hint_text("(a|b)*abb")
+                        .desired_width(f32::INFINITY),
+                )
+                .on_hover_text("| * + ? ( ), ε for the empty word and \\ to escape");
+            }
+
+            if ui.button("Generate").clicked() {
+                let dfa = if app.automaton_from_table {
+                    Dfa::from_table(&app.automaton_table)
+                } else {
+                    Dfa::from_regex(&app.automaton_regex)
+                };
+                match dfa.and_then(|dfa| dfa.to_machine()) {
+                    Ok(machine) => {
+                        app.automaton_error = None;
+                        app.load_undoable("Generate decider", machine);
+                    }
+                    Err(e) => app.automaton_error = Some(e),
+                }
+            }
+
+            if let Some(e) = &app.automaton_error {
+                ui.colored_label(Color32::ORANGE, e);
+            }
+        });
+
+    app.show_automaton = open;
+}
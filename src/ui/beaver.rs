@@ -0,0 +1,120 @@
+use egui::{Color32, DragValue, Grid, ScrollArea, Ui, Window, vec2};
+
+use crate::{
+    TuringApp,
+    beaver::{self, Beaver},
+};
+
+// show the enumeration of small machines and the busy beavers found
+pub fn window(app: &mut TuringApp, ctx: &egui::Context) {
+    let mut open = app.show_beaver;
+
+    Window::new("Busy beavers")
+        .open(&mut open)
+        .default_size(vec2(380.0, 450.0))
+        .show(ctx, |ui| {
+            settings(app, ui);
+            ui.separator();
+            results(app, ui);
+        });
+
+    app.show_beaver = open;
+}
+
+// size of the machines and limits of the enumeration
+fn settings(app: &mut TuringApp, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        ui.label("States");
+        ui.add(DragValue::new(&mut app.beaver_states).range(1..=beaver::MAX_STATES));
+        ui.label("Symbols");
+        ui.add(DragValue::new(&mut app.beaver_symbols).range(2..=beaver::MAX_SYMBOLS));
+        ui.label("Keep");
+        ui.add(DragValue::new(&mut app.beaver_keep).range(1..=100));
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Steps");
+        ui.add(DragValue::new(&mut app.beaver_steps).range(1..=beaver::MAX_STEPS));
+        ui.label("Machines");
+        ui.add(DragValue::new(&mut app.beaver_machines).range(1..=beaver::MAX_MACHINES));
+    });
+
+    // the enumeration runs a slice per frame, see TuringApp::work
+    match &mut app.beaver_result {
+        Some(Ok(enumerator)) if !enumerator.done() => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                if ui.button("Stop").clicked() {
+                    enumerator.stop();
+                }
+            });
+            return;
+        }
+        _ => {}
+    }
+
+    if ui.button("Enumerate").clicked() {
+        app.beaver_result = Some(beaver::Enumerator::new(
+            app.beaver_states,
+            app.beaver_symbols,
+            app.beaver_steps,
+            app.beaver_machines,
+            app.beaver_keep,
+        ));
+    }
+}
+
+// counts of the enumeration and the best machines, which can be loaded
+fn results(app: &mut TuringApp, ui: &mut Ui) {
+    let enumeration = match &app.beaver_result {
+        Some(Ok(enumerator)) => &enumerator.enumeration,
+        Some(Err(e)) => {
+            ui.colored_label(Color32::ORANGE, e);
+            return;
+        }
+        None => return,
+    };
+
+    ui.label(format!(
+        "{} machines: {} halt, {} cycle, {} undecided",
+        enumeration.machines, enumeration.halted, enumeration.cycles, enumeration.undecided
+    ));
+    if enumeration.truncated {
+        ui.colored_label(Color32::ORANGE, "Stopped before the end, the enumeration is incomplete");
+    }
+    if enumeration.undecided > 0 {
+        ui.colored_label(Color32::ORANGE, "Undecided machines may run longer than the best ones")
+            .on_hover_text("Raise the step limit to decide more of them");
+    }
+
+    let mut load = None;
+    ScrollArea::vertical().show(ui, |ui| {
+        ui.collapsing("Most steps", |ui| table(ui, "beaver_steps", &enumeration.by_steps, &mut load));
+        ui.collapsing("Most symbols written", |ui| table(ui, "beaver_written", &enumeration.by_written, &mut load));
+    });
+
+    if let Some(beaver) = load {
+        match beaver.machine() {
+            Ok(tm) => app.load_machine("Load busy beaver", tm),
+            Err(e) => app.messages = vec![e],
+        }
+    }
+}
+
+// best machines of a score
+fn table(ui: &mut Ui, id: &str, beavers: &[Beaver], load: &mut Option<Beaver>) {
+    Grid::new(id).striped(true).show(ui, |ui| {
+        ui.strong("Steps");
+        ui.strong("Written");
+        ui.end_row();
+
+        for beaver in beavers {
+            ui.label(beaver.steps.to_string());
+            ui.label(beaver.written.to_string());
+            if ui.button("Load").on_hover_text(beaver.to_source()).clicked() {
+                *load = Some(beaver.clone());
+            }
+            ui.end_row();
+        }
+    });
+}
@@ -0,0 +1,67 @@
+use egui::{Color32, RichText, ScrollArea, Window, vec2};
+
+use crate::{
+    TuringApp,
+    transform::cleanup::{self, Change, Cleanup},
+};
+
+// show a cleanup of the current machine as a diff against its code, to apply or undo it
+pub fn window(app: &mut TuringApp, ctx: &egui::Context) {
+    let mut open = app.show_cleanup;
+
+    Window::new("Cleanup")
+        .open(&mut open)
+        .default_size(vec2(400.0, 450.0))
+        .show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for cleanup in Cleanup::ALL {
+                    ui.radio_value(&mut app.cleanup, cleanup, cleanup.name());
+                }
+            });
+
+            // the diff is only computed again when the cleanup or the code change
+            let stale = !matches!(&app.cleanup_preview, Some((c, code, _)) if *c == app.cleanup && *code == app.code);
+            if stale {
+                let preview = app
+                    .cleaned_code(app.cleanup)
+                    .map(|cleaned| (cleaned.summary, cleanup::diff(&app.code, &cleaned.code)));
+                app.cleanup_preview = Some((app.cleanup, app.code.clone(), preview));
+            }
+
+            let mut changed = false;
+            match &app.cleanup_preview {
+                Some((_, _, Ok((summary, changes)))) => {
+                    changed = changes.iter().any(|c| !matches!(c, Change::Same(_)));
+                    ui.label(summary);
+                    ui.separator();
+                    ScrollArea::vertical().id_salt("cleanup_diff").max_height(300.0).show(ui, |ui| {
+                        for change in changes {
+                            let line = match change {
+                                Change::Same(line) => RichText::new(format!("  {}", line)),
+                                Change::Removed(line) => RichText::new(format!("- {}", line)).color(Color32::RED),
+                                Change::Added(line) => RichText::new(format!("+ {}", line)).color(Color32::GREEN),
+                            };
+                            ui.label(line.monospace());
+                        }
+                    });
+                }
+                Some((_, _, Err(e))) => {
+                    ui.colored_label(Color32::ORANGE, e);
+                }
+                None => {}
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.add_enabled(changed, egui::Button::new("Apply")).clicked() {
+                    app.apply_cleanup(app.cleanup);
+                }
+                let undo = app.last_edit().map(|s| format!("Undo \"{}\"", s.label));
+                if ui.add_enabled(undo.is_some(), egui::Button::new(undo.unwrap_or("Undo".into()))).clicked() {
+                    app.undo();
+                }
+            });
+        });
+
+    app.show_cleanup = open;
+}
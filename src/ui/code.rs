@@ -1,12 +1,18 @@
 use std::{ffi::OsStr, fs, path::Path};
 
-use super::{button::button, constant::Constant};
-use crate::TuringApp;
+use super::{
+    button::{button, label_colored},
+    constant::Constant,
+    file, transform,
+};
+use crate::{TuringApp, analysis::Problem, source};
 use egui::{
-    CentralPanel, Color32, CornerRadius, Frame, Label, Layout, Margin, RichText, ScrollArea,
+    CentralPanel, CollapsingHeader, Sense, Color32, CornerRadius, Frame, Id, Label, Layout, Margin, RichText, ScrollArea,
     Stroke, TextEdit, Ui, Visuals,
-    style::Selection, Response,
-    text::{Fonts, LayoutJob},
+    style::Selection, Response, Rect, Shape,
+    layers::ShapeIdx,
+    text::{CCursor, CCursorRange, Fonts, LayoutJob},
+    text_edit::TextEditOutput,
     vec2,
 };
 use poll_promise::Promise;
@@ -47,9 +53,21 @@ pub fn ui(app: &mut TuringApp, ui: &mut Ui) {
                         
                         let res = flex.add(item(), load_file_button);
                         load_file(app, res);
+
+                        flex.add_ui(item(), |ui| file::examples_menu(app, ui));
+                        flex.add_ui(item(), |ui| file::import_menu(app, ui));
+                        flex.add_ui(item(), |ui| file::export_menu(app, ui));
+                        flex.add_ui(item(), |ui| transform::menu(app, ui));
                     });
             });
 
+            for message in app.messages.iter() {
+                let warning = label_colored(ui.style_mut(), message, Color32::ORANGE);
+                ui.add(warning);
+            }
+
+            problems(app, ui);
+
             ScrollArea::vertical().show(ui, |ui| {
                 Frame::new().fill(Constant::FOREGROUND).show(ui, |ui| {
                     ui.horizontal_top(|ui| {
@@ -122,13 +140,147 @@ pub fn ui(app: &mut TuringApp, ui: &mut Ui) {
                             .text_color(Color32::WHITE)
                             .font(Constant::get_code_font());
 
-                        ui.add_sized(ui.available_size() - (0.0, 0.0).into(), code_edit);
+                        // highlights are painted behind the text, once it is laid out
+                        let background = ui.painter().add(Shape::Noop);
+                        let output = code_edit
+                            .id(Id::new("code_editor"))
+                            .desired_width(ui.available_width())
+                            .min_size(ui.available_size())
+                            .show(ui);
+                        // the lines of the rules are only known for the code compiled
+                        if output.response.changed() {
+                            app.spans = source::Spans::default();
+                        }
+                        highlight(app, ui, &output, background);
+                        follow_cursor(app, ui, output);
                     });
                 });
             });
         });
 }
 
+// show the problems found in the machine, clicking one selects its state or rule
+fn problems(app: &mut TuringApp, ui: &mut Ui) {
+    if app.problems.is_empty() {
+        return;
+    }
+
+    let mut selected = None;
+    CollapsingHeader::new(RichText::new(format!("Problems ({})", app.problems.len())).color(Color32::ORANGE))
+        .id_salt("problems")
+        .show(ui, |ui| {
+            ScrollArea::vertical().max_height(120.0).show(ui, |ui| {
+                for problem in app.problems.iter() {
+                    let text = RichText::new(format!("{}: {}", problem.lint.name(), problem.message))
+                        .font(Constant::get_small_font())
+                        .color(Color32::WHITE);
+                    if ui.add(Label::new(text).sense(Sense::click())).clicked() {
+                        selected = Some(problem.clone());
+                    }
+                }
+            });
+        });
+
+    match selected {
+        Some(Problem { rule: Some((from, id)), .. }) => app.select_rule(from, id as u8),
+        Some(Problem { state: Some(index), .. }) => app.select_state(index),
+        _ => (),
+    }
+}
+
+// highlight the block of the current state and the rule just taken,
+// scrolling to the rule when it changes
+fn highlight(app: &TuringApp, ui: &mut Ui, output: &TextEditOutput, background: ShapeIdx) {
+    let state = app.state();
+    let mut shapes = vec![];
+
+    if let Some((first, last)) = app.spans.states.get(&state) {
+        let rect = lines_rect(&app.code, output, *first, *last);
+        shapes.push(Shape::rect_filled(rect, CornerRadius::ZERO, Constant::BACKGROUND));
+    }
+
+    let rule = app.active_rule();
+    if let Some(line) = rule {
+        let rect = lines_rect(&app.code, output, line, line);
+        shapes.push(Shape::rect_filled(rect, CornerRadius::ZERO, Constant::SELECTED.gamma_multiply(0.5)));
+
+        let id = ui.id().with("highlighted_rule");
+        if ui.data(|d| d.get_temp::<usize>(id)) != Some(line) {
+            ui.data_mut(|d| d.insert_temp(id, line));
+            ui.scroll_to_rect(rect, None);
+        }
+    }
+
+    ui.painter().set(background, shapes);
+}
+
+// move the cursor to the element selected in the graph, and select in the graph
+// the element under the cursor when it moves
+fn follow_cursor(app: &mut TuringApp, ui: &mut Ui, output: TextEditOutput) {
+    let id = ui.id().with("code_cursor");
+
+    if let Some(line) = app.code_cursor.take() {
+        let index: usize = app.code.lines().take(line).map(|l| l.chars().count() + 1).sum();
+        let mut state = output.state.clone();
+        state.cursor.set_char_range(Some(CCursorRange::one(CCursor::new(index))));
+        state.store(ui.ctx(), output.response.id);
+        output.response.request_focus();
+        ui.scroll_to_rect(lines_rect(&app.code, &output, line, line), None);
+
+        // not a move of the user
+        ui.data_mut(|d| d.insert_temp(id, index));
+        return;
+    }
+
+    if !output.response.has_focus() {
+        return;
+    }
+    let Some(range) = output.cursor_range else {
+        return;
+    };
+
+    let index = range.primary.ccursor.index;
+    if ui.data(|d| d.get_temp::<usize>(id)) == Some(index) {
+        return;
+    }
+    ui.data_mut(|d| d.insert_temp(id, index));
+
+    // line and column of the cursor
+    let mut start = 0;
+    for (line, text) in app.code.lines().enumerate() {
+        let len = text.chars().count();
+        if index <= start + len {
+            let word = word_at(text, index - start);
+            app.select_from_code(line, &word);
+            return;
+        }
+        start += len + 1;
+    }
+}
+
+// identifier around a column of a line
+fn word_at(line: &str, column: usize) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let is_name = |c: &char| c.is_alphanumeric() || *c == '_';
+    let before = chars[..column].iter().rev().take_while(|c| is_name(c)).count();
+    let after = chars[column..].iter().take_while(|c| is_name(c)).count();
+    chars[column - before..column + after].iter().collect()
+}
+
+// rectangle covering lines of the code in the editor, over its whole width
+fn lines_rect(code: &str, output: &TextEditOutput, first: usize, last: usize) -> Rect {
+    let start: usize = code.lines().take(first).map(|l| l.chars().count() + 1).sum();
+    let end: usize = code.lines().take(last + 1).map(|l| l.chars().count() + 1).sum::<usize>().max(1) - 1;
+
+    let top = output.galley.pos_from_ccursor(CCursor::new(start));
+    let bottom = output.galley.pos_from_ccursor(CCursor::new(end));
+
+    Rect::from_x_y_ranges(
+        output.response.rect.x_range(),
+        (output.galley_pos.y + top.top())..=(output.galley_pos.y + bottom.bottom()),
+    )
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn load_file(app: &mut TuringApp, res: Response) {
 
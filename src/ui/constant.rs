@@ -18,6 +18,11 @@ impl Constant {
     pub const CSPRING: f32 = 100.0;
     pub const L: f32 = 200.0;
     pub const MAX_FORCE: f32 = 100000.0;
+    pub const MAX_SPEED: f32 = 100.0;
+    pub const MAX_STEPS_PER_FRAME: usize = 1000;
+    pub const WORK_PER_FRAME: usize = 100_000;
+    pub const ANIMATION_DURATION: f32 = 0.3;
+    pub const MAX_HISTORY: usize = 10000;
     pub fn get_code_font() -> FontId {
         FontId {
             family: egui::FontFamily::Name("Roboto".into()),
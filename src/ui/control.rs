@@ -1,12 +1,13 @@
 use std::fmt::format;
 
-use egui::{vec2, Align, Align2, Button, Color32, Image, Label, Layout, TextEdit, Ui};
+use egui::{vec2, Align, Align2, Button, Color32, Image, Label, Layout, Slider, TextEdit, Ui};
 use egui_flex::{item, Flex, FlexAlign, FlexAlignContent, FlexJustify};
 use turingrs::turing_machine::TuringMachineExecutor;
 
 use crate::TuringApp;
 
 use super::button::{self, button, button_image, label, label_colored, text_edit_single};
+use super::constant::Constant;
 
 // show the control part of the ui
 pub fn ui(app: &mut TuringApp, ui: &mut Ui) {
@@ -39,10 +40,14 @@ pub fn ui(app: &mut TuringApp, ui: &mut Ui) {
             let reset = button_image(flex.style_mut(),egui::include_image!("../../assets/reset.png"));
             let next = button_image(flex.style_mut(),egui::include_image!("../../assets/next.png"));
 
-            flex.add(item(),play);
-            flex.add(item(),pause);
+            if flex.add(item(),play).clicked() {
+                app.running = true;
+            };
+            if flex.add(item(),pause).clicked() {
+                app.running = false;
+            };
             if flex.add(item(),reset).clicked() {
-                app.update_input();
+                app.reset();
             };
             if flex.add(item(),next).clicked() {
                 app.next();
@@ -55,6 +60,14 @@ pub fn ui(app: &mut TuringApp, ui: &mut Ui) {
         .justify(FlexJustify::SpaceAround)
         .show(right, |flex| {
             
+            let speed = Slider::new(&mut app.speed, 1.0..=Constant::MAX_SPEED)
+                .logarithmic(true)
+                .custom_formatter(|v, _| if v as f32 >= Constant::MAX_SPEED { String::from("max") } else { format!("{:.0}", v) })
+                .suffix(" steps/s");
+            flex.add(item(), speed);
+
+            flex.add_ui(item(), |ui| view_menu(app, ui));
+
             let steps = label(flex.style_mut(), &format!("Steps : {}", app.count));
             flex.add(item(), steps);
 
@@ -68,3 +81,20 @@ pub fn ui(app: &mut TuringApp, ui: &mut Ui) {
         }); 
     });
 }
+
+// show the menu of tool windows
+fn view_menu(app: &mut TuringApp, ui: &mut Ui) {
+    let view = button(ui.style_mut(), "View");
+    egui::menu::menu_custom_button(ui, view, |ui| {
+        ui.checkbox(&mut app.show_watch, "Configuration");
+        ui.checkbox(&mut app.show_history, "Space-time diagram");
+        ui.checkbox(&mut app.show_ntm, "Nondeterministic exploration");
+        ui.checkbox(&mut app.show_profile, "Complexity profiler");
+        ui.checkbox(&mut app.show_equivalence, "Equivalence");
+        ui.checkbox(&mut app.show_fuzz, "Fuzzing");
+        ui.checkbox(&mut app.show_beaver, "Busy beavers");
+        ui.checkbox(&mut app.show_automaton, "Automaton to machine");
+        ui.checkbox(&mut app.show_universal, "Universal machine");
+        ui.checkbox(&mut app.show_cleanup, "Cleanup");
+    });
+}
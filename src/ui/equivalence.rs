@@ -0,0 +1,143 @@
+use egui::{Color32, DragValue, ScrollArea, TextEdit, TextStyle, Ui, Window, vec2};
+
+use crate::{TuringApp, examples::EXAMPLES};
+
+// show the bounded comparison of the current machine with a second one
+pub fn window(app: &mut TuringApp, ctx: &egui::Context) {
+    let mut open = app.show_equivalence;
+
+    Window::new("Equivalence")
+        .open(&mut open)
+        .default_size(vec2(400.0, 450.0))
+        .show(ctx, |ui| {
+            second_machine(app, ui);
+            ui.separator();
+            settings(app, ui);
+            ui.separator();
+            report(app, ui);
+        });
+
+    app.show_equivalence = open;
+}
+
+// code of the machine compared with the current one
+fn second_machine(app: &mut TuringApp, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        ui.label("Second machine");
+        if ui.button("Copy current code").clicked() {
+            app.second_code = app.code.clone();
+        }
+        ui.menu_button("Examples", |ui| {
+            for example in EXAMPLES.iter() {
+                if ui.button(example.name).on_hover_text(example.description).clicked() {
+                    app.second_code = example.code.to_string();
+                    ui.close_menu();
+                }
+            }
+        });
+    });
+
+    ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+        ui.add(
+            TextEdit::multiline(&mut app.second_code)
+                .font(TextStyle::Monospace)
+                .hint_text("Paste the code of the machine to compare with")
+                .desired_width(f32::INFINITY)
+                .desired_rows(8),
+        );
+    });
+}
+
+// words and budget of the comparison
+fn settings(app: &mut TuringApp, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        ui.label("Alphabet");
+        ui.add(TextEdit::singleline(&mut app.equivalence_alphabet).desired_width(80.0))
+            .on_hover_text("Symbols of the input words, read from both machines when left empty");
+        ui.label("Length up to");
+        ui.add(DragValue::new(&mut app.equivalence_length).range(0..=12));
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Budget");
+        ui.add(DragValue::new(&mut app.equivalence_budget).range(1..=10_000_000).suffix(" steps"));
+        ui.checkbox(&mut app.equivalence_outputs, "Compare output tapes");
+    });
+
+    // the words are compared a slice per frame, see TuringApp::work
+    match &mut app.equivalence_report {
+        Some(Ok(checker)) if !checker.done() => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(format!("{} words compared", checker.report.checked));
+                if ui.button("Stop").clicked() {
+                    checker.stop();
+                }
+            });
+            return;
+        }
+        _ => {}
+    }
+
+    if ui.button("Compare").clicked() {
+        app.check_equivalence();
+    }
+}
+
+// result of the last comparison
+fn report(app: &mut TuringApp, ui: &mut Ui) {
+    let report = match &app.equivalence_report {
+        Some(Ok(checker)) if checker.done() => &checker.report,
+        Some(Err(e)) => {
+            ui.colored_label(Color32::ORANGE, e);
+            return;
+        }
+        _ => return,
+    };
+
+    match &report.counterexample {
+        Some(c) => {
+            ui.colored_label(Color32::LIGHT_RED, format!("Counterexample: '{}', loaded as input", c.input));
+            ui.label(format!("Current machine: {}", c.first.label()));
+            ui.label(format!("Second machine: {}", c.second.label()));
+            if let Some((first, second)) = &c.outputs {
+                ui.label(format!("Current output: {}", first));
+                ui.label(format!("Second output: {}", second));
+            }
+        }
+        None if report.stopped => {
+            ui.colored_label(
+                Color32::ORANGE,
+                format!("Stopped, same results on the {} words compared", report.checked - report.inconclusive.len()),
+            );
+        }
+        None => {
+            ui.colored_label(
+                Color32::LIGHT_GREEN,
+                format!(
+                    "Same results on the {} words up to length {}",
+                    report.checked - report.inconclusive.len(),
+                    report.max_length
+                ),
+            );
+        }
+    }
+
+    let mut clicked = None;
+    if !report.inconclusive.is_empty() {
+        ui.colored_label(Color32::ORANGE, format!("{} words ran out of budget:", report.inconclusive.len()))
+            .on_hover_text("Nothing can be said on them, try a larger budget");
+        ScrollArea::vertical().id_salt("inconclusive").max_height(80.0).show(ui, |ui| {
+            for word in report.inconclusive.iter() {
+                if ui.link(format!("'{}'", word)).clicked() {
+                    clicked = Some(word.clone());
+                }
+            }
+        });
+    }
+
+    if let Some(word) = clicked {
+        app.input = word;
+        app.update_input();
+    }
+}
@@ -0,0 +1,121 @@
+use egui::Ui;
+use poll_promise::Promise;
+
+use super::button::button;
+use crate::{
+    TuringApp,
+    examples::EXAMPLES,
+    format::{self, Format},
+};
+
+// show the menu of bundled examples
+pub fn examples_menu(app: &mut TuringApp, ui: &mut Ui) {
+    let examples = button(ui.style_mut(), "Examples");
+    egui::menu::menu_custom_button(ui, examples, |ui| {
+        for example in EXAMPLES.iter() {
+            if ui
+                .button(example.name)
+                .on_hover_text(example.description)
+                .clicked()
+            {
+                app.load_example(example);
+                ui.close_menu();
+            }
+        }
+    });
+}
+
+// show the import menu and load the imported machine when the file is read
+pub fn import_menu(app: &mut TuringApp, ui: &mut Ui) {
+    let import = button(ui.style_mut(), "Import");
+    egui::menu::menu_custom_button(ui, import, |ui| {
+        for f in Format::ALL.into_iter().filter(Format::can_import) {
+            if ui.button(f.name()).clicked() {
+                app.import_promise = Some((f, read_file(f)));
+                ui.close_menu();
+            }
+        }
+    });
+
+    let ready = match &app.import_promise {
+        Some((f, promise)) => promise.ready().map(|text| (*f, text.clone())),
+        None => None,
+    };
+
+    if let Some((f, text)) = ready {
+        app.import_promise = None;
+        if let Some(text) = text {
+            match format::import(f, &text) {
+                Ok(imported) => app.load_imported(imported),
+                Err(e) => app.messages = vec![format!("{} import failed: {}", f.name(), e)],
+            }
+        }
+    }
+}
+
+// show the export menu
+pub fn export_menu(app: &mut TuringApp, ui: &mut Ui) {
+    let export = button(ui.style_mut(), "Export");
+    egui::menu::menu_custom_button(ui, export, |ui| {
+        for f in Format::ALL {
+            if ui.button(f.name()).clicked() {
+                match format::export(f, app) {
+                    Ok(content) => write_file(ui, f.name(), f.extensions()[0], content),
+                    Err(e) => app.messages = vec![format!("{} export failed: {}", f.name(), e)],
+                }
+                ui.close_menu();
+            }
+        }
+    });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_file(f: Format) -> Promise<Option<String>> {
+    use rfd::FileDialog;
+
+    Promise::spawn_thread("import_file", move || {
+        FileDialog::new()
+            .add_filter(f.name(), f.extensions())
+            .pick_file()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_file(f: Format) -> Promise<Option<String>> {
+    use rfd::AsyncFileDialog;
+
+    Promise::spawn_local(async move {
+        let file = AsyncFileDialog::new()
+            .add_filter(f.name(), f.extensions())
+            .pick_file()
+            .await?;
+        String::from_utf8(file.read().await).ok()
+    })
+}
+
+/// Save a content to a file chosen by the user
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_file(_ui: &Ui, name: &str, extension: &str, content: String) {
+    use rfd::FileDialog;
+
+    let (name, extension) = (name.to_string(), extension.to_string());
+    std::thread::spawn(move || {
+        if let Some(path) = FileDialog::new()
+            .add_filter(name, &[&extension])
+            .set_file_name(format!("turing.{}", extension))
+            .save_file()
+        {
+            if let Err(e) = std::fs::write(path, content) {
+                log::error!("cannot write file: {}", e);
+            }
+        }
+    });
+}
+
+/// Save a content to a file chosen by the user.
+/// There is no file system on the web, the content goes to the clipboard instead
+#[cfg(target_arch = "wasm32")]
+pub fn write_file(ui: &Ui, _name: &str, _extension: &str, content: String) {
+    ui.ctx().copy_text(content);
+}
@@ -0,0 +1,201 @@
+use egui::{Color32, ComboBox, DragValue, ScrollArea, TextEdit, Ui, Window, vec2};
+
+use crate::{
+    TuringApp,
+    fuzz::OracleKind,
+    source,
+};
+
+// show the fuzzing of the machine against an oracle, and the tests saved in the code
+pub fn window(app: &mut TuringApp, ctx: &egui::Context) {
+    let mut open = app.show_fuzz;
+
+    Window::new("Fuzzing")
+        .open(&mut open)
+        .default_size(vec2(380.0, 450.0))
+        .show(ctx, |ui| {
+            oracle(app, ui);
+            ui.separator();
+            settings(app, ui);
+            campaign(app, ui);
+            ui.separator();
+            tests(app, ui);
+        });
+
+    app.show_fuzz = open;
+}
+
+// what the verdicts of the machine are checked against
+fn oracle(app: &mut TuringApp, ui: &mut Ui) {
+    ComboBox::from_label("Oracle")
+        .selected_text(app.fuzz_oracle.name())
+        .show_ui(ui, |ui| {
+            for kind in OracleKind::ALL {
+                ui.selectable_value(&mut app.fuzz_oracle, kind, kind.name());
+            }
+        });
+
+    match app.fuzz_oracle {
+        OracleKind::Regex => {
+            ui.add(TextEdit::singleline(&mut app.fuzz_regex).hint_text("(ab)*").desired_width(f32::INFINITY))
+                .on_hover_text("Matched against the whole input");
+        }
+        OracleKind::List => {
+            ScrollArea::vertical().id_salt("fuzz_list").max_height(100.0).show(ui, |ui| {
+                ui.add(
+                    TextEdit::multiline(&mut app.fuzz_list)
+                        .hint_text("One accepted input per line, ε for the empty one")
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(4),
+                );
+            });
+        }
+        OracleKind::Machine => {
+            ui.horizontal(|ui| {
+                ui.label("The second machine of the Equivalence window");
+                if ui.button("Edit").clicked() {
+                    app.show_equivalence = true;
+                }
+            });
+        }
+    }
+}
+
+// random inputs and budget of the campaign
+fn settings(app: &mut TuringApp, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        ui.label("Alphabet");
+        ui.add(TextEdit::singleline(&mut app.fuzz_alphabet).desired_width(80.0))
+            .on_hover_text("Symbols of the inputs, read from the machine when left empty");
+        ui.label("Length up to");
+        ui.add(DragValue::new(&mut app.fuzz_length).range(0..=100));
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Runs");
+        ui.add(DragValue::new(&mut app.fuzz_runs).range(1..=100_000));
+        ui.label("Budget");
+        ui.add(DragValue::new(&mut app.fuzz_budget).range(1..=10_000_000).suffix(" steps"));
+    });
+
+    // the inputs are run a slice per frame, see TuringApp::work
+    match &mut app.fuzz_campaign {
+        Some(Ok(fuzzer)) if !fuzzer.done() => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                let campaign = &fuzzer.campaign;
+                ui.label(format!("{} / {} runs, {} failures", campaign.runs, fuzzer.runs(), campaign.failures.len()));
+                if ui.button("Stop").clicked() {
+                    fuzzer.stop();
+                }
+            });
+        }
+        _ => {
+            if ui.button("Fuzz").clicked() {
+                app.fuzz();
+            }
+        }
+    }
+}
+
+// result of the last campaign, each failure being loadable and savable as a test
+fn campaign(app: &mut TuringApp, ui: &mut Ui) {
+    let campaign = match &app.fuzz_campaign {
+        Some(Ok(fuzzer)) if fuzzer.done() => &fuzzer.campaign,
+        Some(Err(e)) => {
+            ui.colored_label(Color32::ORANGE, e);
+            return;
+        }
+        _ => return,
+    };
+
+    let stopped = if campaign.stopped { "stopped after " } else { "" };
+    let summary = format!("{}{} runs, {} out of budget", stopped, campaign.runs, campaign.timeouts);
+    if campaign.failures.is_empty() {
+        ui.colored_label(Color32::LIGHT_GREEN, format!("{}, no failure", summary));
+        return;
+    }
+    ui.colored_label(Color32::LIGHT_RED, format!("{}, {} failures:", summary, campaign.failures.len()));
+
+    let mut load = None;
+    let mut save = None;
+    ScrollArea::vertical().id_salt("fuzz_failures").max_height(150.0).show(ui, |ui| {
+        for failure in campaign.failures.iter() {
+            ui.horizontal(|ui| {
+                let verdict = if failure.expected { "should accept" } else { "should refuse" };
+                if ui
+                    .link(format!("'{}' {}", failure.input, verdict))
+                    .on_hover_text(format!("Shrunk from '{}'", failure.original))
+                    .clicked()
+                {
+                    load = Some(failure.input.clone());
+                }
+                if ui.small_button("Save as test").clicked() {
+                    save = Some((failure.input.clone(), failure.expected));
+                }
+            });
+        }
+    });
+
+    if let Some(input) = load {
+        app.input = input;
+        app.update_input();
+    }
+    if let Some((input, accepted)) = save {
+        app.save_test(&input, accepted);
+    }
+}
+
+// tests saved in the code
+fn tests(app: &mut TuringApp, ui: &mut Ui) {
+    let count = source::tests(&app.code).len();
+    ui.horizontal(|ui| {
+        ui.label(format!("{} tests in the code", count))
+            .on_hover_text(format!("Saved as comments: {}", source::test_comment("abba", true)));
+
+        // the tests are run a slice per frame, see TuringApp::work
+        match &mut app.test_failures {
+            Some(Ok(tester)) if !tester.done() => {
+                ui.spinner();
+                ui.label(format!("{} / {}", tester.checked, tester.tests.len()));
+                if ui.button("Stop").clicked() {
+                    tester.stop();
+                }
+            }
+            _ => {
+                if ui.button("Run tests").clicked() {
+                    app.run_tests();
+                }
+            }
+        }
+    });
+
+    let mut load = None;
+    match &app.test_failures {
+        Some(Ok(tester)) if !tester.done() => {}
+        Some(Ok(tester)) if tester.failures.is_empty() && tester.checked < tester.tests.len() => {
+            ui.colored_label(Color32::ORANGE, format!("Stopped, the {} tests run pass", tester.checked));
+        }
+        Some(Ok(tester)) if tester.failures.is_empty() => {
+            ui.colored_label(Color32::LIGHT_GREEN, "Every test passes");
+        }
+        Some(Ok(tester)) => {
+            for failure in tester.failures.iter() {
+                let got = failure.outcome.label();
+                let expected = if failure.expected { "accepts" } else { "refuses" };
+                if ui.link(format!("'{}' {}, got {}", failure.input, expected, got)).clicked() {
+                    load = Some(failure.input.clone());
+                }
+            }
+        }
+        Some(Err(e)) => {
+            ui.colored_label(Color32::ORANGE, e);
+        }
+        None => {}
+    }
+
+    if let Some(input) = load {
+        app.input = input;
+        app.update_input();
+    }
+}
@@ -43,36 +43,54 @@ pub fn ui(app: &mut TuringApp, ui: &mut Ui) {
             let mut inner_rect = Rect::NAN;
             let mut scene_rect = app.graph_rect;
 
-            // apply force on node
-            let (center, is_stable) = apply_organic_force(app);
+            // apply force on node, unless the layout comes from an imported file
+            let (center, is_stable) = if app.organic_layout {
+                apply_organic_force(app)
+            } else {
+                (graph_center(app), true)
+            };
             app.is_stable = is_stable;
 
+            let folded = folded_states(app);
+            let shown = |index: u8| folded.get(&index).copied().unwrap_or(index);
+            let current = shown(app.state());
+
             // scene for graph resize/move
             let response = Scene::new()
                 .show(ui, &mut scene_rect, |ui| {
 
 
                     // group transition by source and target state
-                    let mut transitions: HashMap<(u8,u8), Vec<(TuringTransition, &Transition)>> = HashMap::new();
+                    // the rules of a collapsed macro leave from the macro state, without labels
+                    let mut transitions: HashMap<(u8,u8), Vec<(TuringTransition, &Transition, bool)>> = HashMap::new();
 
                     for (index, _state) in app.states_hash.iter() {
 
                         for t in _state.transitions.iter() {
                             let x = app.turing.turing_machine.states[*index as usize].get_transition(t.id).clone();
+                            let (from, to) = (shown(*index), shown(x.index_to_state));
+                            let labeled = !folded.contains_key(index);
 
-                            match transitions.entry((*index, x.index_to_state)) {
-                                Entry::Occupied(mut e) => { e.get_mut().push((x,t)); },
-                                Entry::Vacant(e) => { e.insert(vec![(x,t)]); },
+                            // rules inside a collapsed macro
+                            if from == to && !labeled && folded.contains_key(&x.index_to_state) {
+                                continue;
+                            }
+
+                            match transitions.entry((from, to)) {
+                                Entry::Occupied(mut e) => { e.get_mut().push((x,t,labeled)); },
+                                Entry::Vacant(e) => { e.insert(vec![(x,t,labeled)]); },
                             }
                         }
                     }
 
+                    let mut clicked_rule = None;
+
                     // draw group of transitions
                     for ((from,to), trans) in transitions {
                         let force_switch = app.turing.get_turing_machine().get_transition_index(from, to).is_some() && from > to;
 
                         let mut rules: Vec<(bool, &Transition)> = vec![];
-                        for (tt, t) in trans.iter() {
+                        for (tt, t, _) in trans.iter().filter(|(_, _, labeled)| *labeled) {
                             rules.push((app.current_step.transition_taken == *tt, t));
                         }
 
@@ -89,21 +107,33 @@ pub fn ui(app: &mut TuringApp, ui: &mut Ui) {
                             center,
                             force_switch,
                             if app.selected_transition.is_some_and(|(f,t)| f == from && t == to) {Constant::SELECTED} else {Constant::ARROW},
-                            (from, to)
                         );
 
-                        if let Some(ti) = clicked {
-                            app.selected_transition = Some(ti);
-                            app.selected_node = None;
+                        if let Some(id) = clicked {
+                            clicked_rule = Some((from, id));
                         }
                     }
 
+                    if let Some((from, id)) = clicked_rule {
+                        app.select_rule(from, id);
+                    }
+
                     let mut responses: Vec<(Response, u8)> = vec![];
 
                     // draw nodes
                     for (index, state) in app.states_hash.iter_mut() {
+                        if shown(*index) != *index {
+                            continue;
+                        }
                         let pos = state.position;
 
+                        // macro states show whether they are collapsed
+                        let name = match app.macros.iter().find(|m| m.name == state.name) {
+                            Some(m) if app.expanded_macros.contains(&m.name) => format!("{} ▾", state.name),
+                            Some(m) => format!("{} ▸ {}", state.name, m.machine),
+                            None => state.name.clone(),
+                        };
+
                         let response = draw_node(
                             ui,
                             pos,
@@ -114,8 +144,8 @@ pub fn ui(app: &mut TuringApp, ui: &mut Ui) {
                             } else {
                                 state.color
                             },
-                            &state.name,
-                            app.turing.get_state_pointer() == *index
+                            &name,
+                            current == *index
                         );
 
                         responses.push((response, *index));
@@ -144,14 +174,28 @@ pub fn ui(app: &mut TuringApp, ui: &mut Ui) {
 
                                 app.selected_node = None;
                             } else {
-                                app.selected_node = Some(index);
-                                app.selected_transition = None;
+                                app.select_state(index);
                             }
                         }
 
                         if response.dragged() {
                             app.states_hash.get_mut(&index).unwrap().position = response.interact_pointer_pos().unwrap();
                         }
+
+                        let name = &app.states_hash[&index].name;
+                        if let Some(m) = app.macros.iter().find(|m| m.name == *name).map(|m| m.name.clone()) {
+                            response.context_menu(|ui| {
+                                let expanded = app.expanded_macros.contains(&m);
+                                if ui.button(if expanded { "Collapse macro" } else { "Expand macro" }).clicked() {
+                                    if expanded {
+                                        app.expanded_macros.remove(&m);
+                                    } else {
+                                        app.expanded_macros.insert(m.clone());
+                                    }
+                                    ui.close_menu();
+                                }
+                            });
+                        }
                     }
 
                     inner_rect = ui.min_rect();
@@ -230,7 +274,7 @@ fn draw_node(
     ui.allocate_rect(rect, Sense::click_and_drag())
 }
 
-// Draw the transition of the turing machine
+// Draw the transition of the turing machine, returning the id of the rule clicked
 fn draw_transition(
     ui: &mut Ui,
     source: Pos2,
@@ -239,8 +283,7 @@ fn draw_transition(
     graph_center: Vec2,
     reverse: bool,
     color: Color32,
-    transition_id: (u8, u8)
-) -> Option<(u8, u8)> {
+) -> Option<u8> {
     let mut delta = (if reverse {
         source - target
     } else {
@@ -321,7 +364,7 @@ fn draw_transition(
         Stroke::NONE,
     ));
 
-    let mut clicked: Option<(u8, u8)> = None;
+    let mut clicked: Option<u8> = None;
 
     for (i,(select, t)) in transitions.iter().enumerate() {
         
@@ -339,8 +382,7 @@ fn draw_transition(
 
         ui.painter().rect_stroke(response.rect, CornerRadius::ZERO, Stroke::new(1.0, Color32::CYAN), StrokeKind::Inside);
         if response.clicked() {
-            clicked = Some(transition_id);
-            println!("{} {} {} {:?}", response.interact_pointer_pos().unwrap(), rect, t.text, transition_id);
+            clicked = Some(t.id);
         }
     };
     
@@ -371,6 +413,31 @@ fn cubicbeziercurve(points: [Pos2; 4], t: f32) -> Vec2 {
     Vec2::new(x, y)
 }
 
+// states of the collapsed macros, mapped to the macro state shown in their place
+fn folded_states(app: &TuringApp) -> HashMap<u8, u8> {
+    let indices = &app.turing.turing_machine.name_index_hashmap;
+    let mut folded = HashMap::new();
+
+    for m in app.macros.iter().filter(|m| !app.expanded_macros.contains(&m.name)) {
+        let Some(entry) = indices.get(&m.name) else {
+            continue;
+        };
+        for index in m.states.iter().filter_map(|s| indices.get(s)) {
+            folded.insert(*index, *entry);
+        }
+    }
+    folded
+}
+
+// compute the center of the graph
+fn graph_center(app: &TuringApp) -> Vec2 {
+    let mut center = Vec2::ZERO;
+    for state in app.states_hash.values() {
+        center += state.position.to_vec2();
+    }
+    center / app.states_hash.len().max(1) as f32
+}
+
 // apply a force on the node for organic layout
 fn apply_organic_force(app: &mut TuringApp) -> (Vec2, bool) {
     let k = app.turing.get_turing_machine().name_index_hashmap.len();
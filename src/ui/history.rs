@@ -0,0 +1,135 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use egui::{
+    Color32, ComboBox, CornerRadius, Rect, ScrollArea, Sense, Slider, Stroke, StrokeKind, Window,
+    ecolor::Hsva, pos2, vec2,
+};
+
+use crate::{TuringApp, tape};
+use super::{constant::Constant, rubans};
+
+// show the space-time diagram of the execution: a row per step, a column per cell.
+// Clicking a row goes back to this step.
+pub fn window(app: &mut TuringApp, ctx: &egui::Context) {
+    let mut open = app.show_history;
+
+    Window::new("Space-time diagram")
+        .open(&mut open)
+        .default_size(vec2(400.0, 300.0))
+        .show(ctx, |ui| {
+            let rubans_count = app.turing.get_turing_machine().k as usize + 1;
+            app.history_ribbon = app.history_ribbon.min(rubans_count - 1);
+
+            ui.horizontal(|ui| {
+                ComboBox::from_id_salt("history_ribbon")
+                    .selected_text(rubans::title(app, app.history_ribbon))
+                    .show_ui(ui, |ui| {
+                        for i in 0..rubans_count {
+                            let name = rubans::title(app, i);
+                            ui.selectable_value(&mut app.history_ribbon, i, name);
+                        }
+                    });
+                ui.add(Slider::new(&mut app.history_zoom, 2.0..=30.0).text("Zoom"));
+            });
+
+            if app.history.len() >= Constant::MAX_HISTORY {
+                ui.colored_label(
+                    Color32::ORANGE,
+                    format!("Only the first {} steps are recorded", Constant::MAX_HISTORY),
+                );
+            }
+
+            diagram(app, ui);
+        });
+
+    app.show_history = open;
+}
+
+// draw the rows of the diagram that are visible in the scroll area
+fn diagram(app: &mut TuringApp, ui: &mut egui::Ui) {
+    let size = app.history_zoom;
+    let index = app.history_ribbon;
+
+    // cells added to the left of the ribbon before every row, so that a cell stays
+    // in the same column when the ribbon grows to the left
+    let mut origins = Vec::with_capacity(app.history.len());
+    let mut origin = app.initial_origins.get(index).copied().unwrap_or(0);
+    for (row, step) in app.history.iter().enumerate() {
+        if row > 0 {
+            origin += tape::grown_left(&app.history[row - 1], step, index);
+        }
+        origins.push(origin);
+    }
+    let max_origin = origins.last().copied().unwrap_or(0);
+
+    // every cell ever reached, counted in graphemes as on the rubans
+    let columns = app
+        .history
+        .iter()
+        .zip(&origins)
+        .map(|(step, origin)| {
+            let (chars, pointer) = tape::ribbon(step, index);
+            let cells = tape::cells(chars, pointer);
+            cells.symbols.len().max(cells.head as usize + 1) + max_origin - origin
+        })
+        .max()
+        .unwrap_or(0);
+
+    let mut clicked = None;
+
+    ScrollArea::both().auto_shrink(false).show_viewport(ui, |ui, viewport| {
+        let (rect, response) = ui.allocate_exact_size(
+            vec2(columns as f32 * size, app.history.len() as f32 * size),
+            Sense::click(),
+        );
+
+        let first = (viewport.top() / size).floor().max(0.0) as usize;
+        let last = ((viewport.bottom() / size).ceil() as usize).min(app.history.len());
+
+        for row in first..last {
+            let (chars, pointer) = tape::ribbon(&app.history[row], index);
+            let cells = tape::cells(chars, pointer);
+            let top = rect.top() + row as f32 * size;
+            let left = rect.left() + (max_origin - origins[row]) as f32 * size;
+
+            for (cell, symbol) in cells.symbols.iter().enumerate() {
+                let r = Rect::from_min_size(pos2(left + cell as f32 * size, top), vec2(size, size));
+                ui.painter().rect_filled(r, CornerRadius::ZERO, symbol_color(symbol));
+            }
+
+            let head = Rect::from_min_size(
+                pos2(left + cells.head as f32 * size, top),
+                vec2(size, size),
+            );
+            ui.painter().rect_stroke(head, CornerRadius::ZERO, Stroke::new(1.5, Color32::WHITE), StrokeKind::Inside);
+
+            // the step shown on the rubans
+            if row == app.count {
+                let line = Rect::from_min_size(pos2(rect.left(), top), vec2(rect.width(), size));
+                ui.painter().rect_stroke(line, CornerRadius::ZERO, Stroke::new(1.0, Color32::YELLOW), StrokeKind::Outside);
+            }
+        }
+
+        if response.clicked() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                clicked = Some(((pos.y - rect.top()) / size) as usize);
+            }
+        }
+        response.on_hover_text("Click a row to go to this step");
+    });
+
+    if let Some(row) = clicked.filter(|r| *r < app.history.len()) {
+        app.goto_step(row);
+    }
+}
+
+// color of a symbol, derived from its hash so that it stays the same between steps
+fn symbol_color(symbol: &str) -> Color32 {
+    if tape::is_blank(symbol) {
+        return Constant::BACKGROUND2;
+    }
+    let mut hasher = DefaultHasher::new();
+    symbol.hash(&mut hasher);
+    let hue = (hasher.finish() % 360) as f32 / 360.0;
+    Hsva::new(hue, 0.6, 0.85, 1.0).into()
+}
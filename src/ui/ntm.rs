@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+
+use egui::{Color32, DragValue, Pos2, ScrollArea, Sense, Stroke, Ui, Window, pos2, vec2};
+
+use crate::{
+    TuringApp,
+    ntm::{Status, Tree},
+    source,
+};
+use super::constant::Constant;
+
+/// Space between two nodes of the tree, and radius of a node
+const SPACING: f32 = 24.0;
+const RADIUS: f32 = 6.0;
+
+// show the nondeterministic exploration: its budget, its result and the computation tree.
+// Clicking a node replays the branch leading to it.
+pub fn window(app: &mut TuringApp, ctx: &egui::Context) {
+    let mut open = app.show_ntm;
+
+    Window::new("Nondeterministic exploration")
+        .open(&mut open)
+        .default_size(vec2(400.0, 350.0))
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Depth");
+                ui.add(DragValue::new(&mut app.ntm_depth).range(1..=100_000));
+                ui.label("Nodes");
+                ui.add(DragValue::new(&mut app.ntm_nodes).range(1..=100_000));
+                if ui.button("Explore").clicked() {
+                    app.explore();
+                }
+            });
+
+            let Some(tree) = &app.ntm_tree else {
+                ui.label("Explores every rule that applies, breadth-first from the input.");
+                return;
+            };
+
+            ui.label(summary(tree));
+
+            let accepting = tree.accepting;
+            let replaying = app.selected_branch.filter(|_| app.branch.is_some());
+            ui.horizontal(|ui| {
+                if let Some(node) = accepting {
+                    if ui.button("Replay shortest accepting path").clicked() {
+                        app.replay_branch(node);
+                    }
+                }
+                if replaying.is_some() && ui.button("Leave branch").clicked() {
+                    app.leave_branch();
+                }
+            });
+
+            if let Some(node) = replaying {
+                let tree = app.ntm_tree.as_ref().unwrap();
+                let state = source::state_name(&app.turing.turing_machine, tree.nodes[node].state);
+                ui.label(format!("Replaying the branch to '{}' at depth {}", state, tree.nodes[node].depth));
+            }
+
+            ui.separator();
+            if let Some(node) = draw_tree(app, ui) {
+                app.replay_branch(node);
+            }
+        });
+
+    app.show_ntm = open;
+}
+
+// result of the exploration
+fn summary(tree: &Tree) -> String {
+    match tree.accepting {
+        Some(node) => format!(
+            "Accepted: the shortest accepting path has {} steps ({} configurations explored)",
+            tree.nodes[node].depth,
+            tree.nodes.len()
+        ),
+        None if tree.truncated => format!(
+            "No accepting branch within the budget ({} configurations explored)",
+            tree.nodes.len()
+        ),
+        None => format!("Refused: every branch halts without accepting ({} configurations)", tree.nodes.len()),
+    }
+}
+
+// draw the computation tree, a row per depth, returning the node clicked
+fn draw_tree(app: &TuringApp, ui: &mut Ui) -> Option<usize> {
+    let tree = app.ntm_tree.as_ref()?;
+
+    // nodes are in breadth-first order, so every level is contiguous
+    let mut columns = vec![0usize; tree.nodes.len()];
+    let mut widths: Vec<usize> = vec![];
+    for (i, node) in tree.nodes.iter().enumerate() {
+        if widths.len() <= node.depth {
+            widths.push(0);
+        }
+        columns[i] = widths[node.depth];
+        widths[node.depth] += 1;
+    }
+    let width = widths.iter().copied().max().unwrap_or(0);
+
+    // nodes of the branch being replayed
+    let mut branch = HashSet::new();
+    let mut current = app.selected_branch.filter(|_| app.branch.is_some());
+    while let Some(i) = current {
+        branch.insert(i);
+        current = tree.nodes[i].parent;
+    }
+
+    let mut clicked = None;
+    ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+        let (rect, response) = ui.allocate_exact_size(
+            vec2(width as f32 * SPACING + SPACING, widths.len() as f32 * SPACING + SPACING),
+            Sense::click(),
+        );
+        let position = |i: usize| -> Pos2 {
+            rect.left_top() + vec2(columns[i] as f32 + 1.0, tree.nodes[i].depth as f32 + 1.0) * SPACING
+        };
+
+        for (i, node) in tree.nodes.iter().enumerate() {
+            if let Some(parent) = node.parent {
+                let color = if branch.contains(&i) { Color32::YELLOW } else { Constant::BORDER2 };
+                ui.painter().line_segment([position(parent), position(i)], Stroke::new(1.0, color));
+            }
+        }
+
+        for (i, node) in tree.nodes.iter().enumerate() {
+            let fill = match node.status {
+                Status::Accepted => Color32::GREEN,
+                Status::Refused => Color32::RED,
+                Status::Inner => Color32::WHITE,
+                Status::Open => Constant::FOREGROUND,
+            };
+            let stroke = if branch.contains(&i) { Stroke::new(2.0, Color32::YELLOW) } else { Stroke::NONE };
+            ui.painter().circle(position(i), RADIUS, fill, stroke);
+        }
+
+        if let Some(pointer) = response.hover_pos() {
+            let hovered = (0..tree.nodes.len()).find(|i| position(*i).distance(pointer) <= RADIUS + 2.0);
+            if let Some(i) = hovered {
+                let state = source::state_name(&app.turing.turing_machine, tree.nodes[i].state);
+                let response = response.on_hover_text(format!("'{}' at depth {}", state, tree.nodes[i].depth));
+                if response.clicked() {
+                    clicked = Some(i);
+                }
+            }
+        }
+    });
+    clicked
+}
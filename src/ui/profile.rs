@@ -0,0 +1,116 @@
+use egui::{Color32, DragValue, TextEdit, Ui, Window, vec2};
+use egui_plot::{Legend, Line, Plot, PlotPoints, Points};
+
+use crate::{
+    TuringApp,
+    execute::Outcome,
+    profile::{self, Sample},
+};
+use super::file;
+
+// show the complexity profiler: steps and space used against the input length
+pub fn window(app: &mut TuringApp, ctx: &egui::Context) {
+    let mut open = app.show_profile;
+
+    Window::new("Complexity profiler")
+        .open(&mut open)
+        .default_size(vec2(450.0, 400.0))
+        .show(ctx, |ui| {
+            settings(app, ui);
+
+            if let Some(e) = &app.profile_error {
+                ui.colored_label(Color32::ORANGE, e);
+            }
+            let samples = app.profiler.as_ref().map(|p| p.samples.as_slice()).unwrap_or_default();
+            if samples.is_empty() {
+                return;
+            }
+
+            let timeouts = samples.iter().filter(|s| s.outcome == Outcome::Timeout).count();
+            if timeouts > 0 {
+                ui.colored_label(Color32::ORANGE, format!("{} runs out of budget are left out of the fit", timeouts));
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Export CSV").clicked() {
+                    file::write_file(ui, "CSV", "csv", profile::to_csv(samples));
+                }
+            });
+
+            ui.columns_const(|[steps, space]| {
+                plot(steps, "steps", samples, |s| s.steps);
+                plot(space, "space", samples, |s| s.space);
+            });
+        });
+
+    app.show_profile = open;
+}
+
+// inputs and budget of the profile
+fn settings(app: &mut TuringApp, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        ui.radio_value(&mut app.profile_random, false, "Pattern");
+        ui.radio_value(&mut app.profile_random, true, "Random");
+    });
+
+    ui.horizontal(|ui| {
+        if app.profile_random {
+            ui.label("Alphabet");
+            ui.add(TextEdit::singleline(&mut app.profile_alphabet).desired_width(80.0))
+                .on_hover_text("Symbols of the inputs, read from the machine when left empty");
+            ui.label("Samples");
+            ui.add(DragValue::new(&mut app.profile_count).range(1..=100));
+        } else {
+            ui.add(TextEdit::singleline(&mut app.profile_pattern).hint_text("a^n b^n").desired_width(160.0))
+                .on_hover_text("Symbols and groups repeated with ^: a^n b^n, (ab)^2n, 0^3 1^n");
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("n up to");
+        ui.add(DragValue::new(&mut app.profile_max).range(0..=1000));
+        ui.label("Budget");
+        ui.add(DragValue::new(&mut app.profile_budget).range(1..=10_000_000).suffix(" steps"));
+
+        // the inputs are run a slice per frame, see TuringApp::work
+        match &mut app.profiler {
+            Some(profiler) if !profiler.done() => {
+                ui.spinner();
+                if ui.button("Stop").clicked() {
+                    profiler.stop();
+                }
+            }
+            _ => {
+                if ui.button("Run").clicked() {
+                    app.profile();
+                }
+            }
+        }
+    });
+}
+
+// plot a cost of the runs against the input length, with the growth fitting it best
+fn plot(ui: &mut Ui, name: &str, samples: &[Sample], cost: impl Fn(&Sample) -> usize) {
+    let worst = profile::worst_case(samples, &cost);
+    let fit = profile::fit(&worst);
+
+    ui.label(match &fit {
+        Some(fit) => format!("{} ≈ {}", name, fit),
+        None => format!("{}: not enough runs to fit", name),
+    });
+
+    let points: PlotPoints = samples.iter().map(|s| [s.length() as f64, cost(s) as f64]).collect();
+    let last = worst.last().map(|(n, _)| *n).unwrap_or(0.0);
+
+    Plot::new(format!("profile_{}", name))
+        .legend(Legend::default())
+        .height(ui.available_height().max(150.0))
+        .x_axis_label("input length")
+        .show(ui, |plot| {
+            plot.points(Points::new(points).radius(2.5).name(name));
+            if let Some(fit) = fit {
+                let curve = PlotPoints::from_explicit_callback(move |n| fit.apply(n), 0.0..=last, 100);
+                plot.line(Line::new(curve).name(fit.growth.name()));
+            }
+        });
+}
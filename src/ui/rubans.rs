@@ -1,18 +1,28 @@
 use egui::{
-    scroll_area::ScrollBarVisibility, Color32, CornerRadius, FontFamily, FontId, Frame, Label, Layout, Margin, Rect, RichText, ScrollArea, Sense, Stroke, Ui
+    Align, Align2, Checkbox, Layout, Color32, CornerRadius, FontFamily, FontId, Frame, Key, Label, Margin, Rect, Response, RichText, Sense, Stroke, StrokeKind, TextEdit, Ui, pos2, vec2
 };
 
+use egui_flex::{item, Flex, FlexAlign};
 use turingrs::turing_machine::TuringExecutor;
 use unicode_segmentation::UnicodeSegmentation;
-use crate::TuringApp;
-use super::constant::Constant;
+use crate::{source::{direction_to_str, BLANK}, tape, TuringApp};
+use super::{button::{button, text_edit_single}, constant::Constant};
+
+/// Width of a square and of the space between two squares
+const SQUARE: f32 = 30.0;
+const STEP: f32 = 35.0;
+/// Height of the indices written above the squares
+const INDEX_HEIGHT: f32 = 14.0;
+/// Width of the header column of a ruban
+const HEADER_WIDTH: f32 = 120.0;
 
 // show the rubans part of the gui
 pub fn ui(app: &mut TuringApp, ui: &mut Ui) {
 
-    let rubans_count = app.turing.get_turing_machine().k+1;
-    
-    let frame = Frame::new()
+    let rubans_count = app.turing.get_turing_machine().k as usize + 1;
+    app.tape_centers.resize(rubans_count, 0.0);
+
+    Frame::new()
         .inner_margin(Margin::same(10))
         .outer_margin(Margin::same(0))
         .corner_radius(CornerRadius::same(5))
@@ -23,81 +33,307 @@ pub fn ui(app: &mut TuringApp, ui: &mut Ui) {
 
                 ui.spacing_mut().item_spacing = (5.0, 8.0).into();
 
-                let width = ui.available_width();
-                let left_width = width - 
-                    (
-                        ((width+5.0) / 35.0) + 2.0
-                        + (1 - ((width+5.0) / 35.0).floor() as usize%2) as f32).floor() * 35.0 + 5.0;
-
-                ScrollArea::horizontal()
-                    .enable_scrolling(false)
-                    .scroll_bar_visibility(ScrollBarVisibility::AlwaysHidden)
-                    .horizontal_scroll_offset(-left_width/2.0)
-                    .show(ui, |ui| {
-                        for i in 0..rubans_count {
-                            ruban(app, ui, i.into(), width);
-                        }
-                    })
+                navigation(app, ui);
+
+                for i in 0..rubans_count {
+                    ui.horizontal(|ui| ruban(app, ui, i));
+                }
             });
         });
-    
-    ui.painter().rect_stroke(
-        Rect::from_center_size(
-            frame.response.rect.center(),
-            (30.0, (rubans_count * 30 + (rubans_count - 1) * 5) as f32).into()
-        ),
-        CornerRadius::ZERO,
-        Stroke::new(5.0, Constant::BORDER2),
-        egui::StrokeKind::Outside
-    );
 }
 
-// display a ruban
-fn ruban(app: &mut TuringApp, ui: &mut Ui, index: usize, width: f32) {
+// show the navigation bar of the rubans
+fn navigation(app: &mut TuringApp, ui: &mut Ui) {
+    Flex::horizontal()
+        .align_items(FlexAlign::Center)
+        .show(ui, |flex| {
+            flex.add(item(), Checkbox::new(&mut app.follow_head, "Follow head"));
+
+            let field = text_edit_single(flex.style_mut(), &mut app.jump_index)
+                .hint_text("Index")
+                .desired_width(60.0);
+            let go = button(flex.style_mut(), "Go");
+            let response = flex.add(item(), field);
+            let enter = response.lost_focus() && response.ctx.input(|i| i.key_pressed(Key::Enter));
+
+            if flex.add(item(), go).clicked() || enter {
+                if let Ok(index) = app.jump_index.trim().parse::<i32>() {
+                    app.follow_head = false;
+                    app.tape_centers.iter_mut().for_each(|c| *c = index as f32);
+                }
+            }
+        });
+}
+
+// display a ruban, cells can be edited and the head moved with a right click.
+// Dragging or scrolling over the ruban pans it. Cells are numbered from the first cell
+// of the input, the cells added to the left having negative indices.
+fn ruban(app: &mut TuringApp, ui: &mut Ui, index: usize) {
+
+    let (chars, pointer) = tape::ribbon(&app.current_step, index);
+    let cells = tape::cells(chars, pointer);
+    let origin = app.origin(index) as i32;
+    let pointer = cells.head - origin;
+
+    header(app, ui, index, pointer, cells.get(cells.head));
+    if app.collapsed_ribbons.contains(&index) {
+        return;
+    }
+    let width = ui.available_width();
+
+    // animation of the last step, sliding the head and flashing the written cell
+    let progress = animation_progress(app, ui);
+    let previous = app.previous_step.as_ref().filter(|_| progress.is_some()).map(|step| {
+        let (chars, pointer) = tape::ribbon(step, index);
+        let grown = tape::grown_left(step, &app.current_step, index) as i32;
+        tape::cells(chars, pointer).head - (origin - grown)
+    });
+    let head = match (previous, progress) {
+        (Some(prev), Some(t)) => prev as f32 + (pointer - prev) as f32 * t,
+        _ => pointer as f32,
+    };
+    let flash = match (previous, progress) {
+        (Some(prev), Some(t)) if index > 0 => Some((prev, 1.0 - t)),
+        _ => None,
+    };
+
+    if app.follow_head {
+        app.tape_centers[index] = head;
+    }
+
+    let (row, pan) = ui.allocate_exact_size(vec2(width, INDEX_HEIGHT + SQUARE), Sense::drag());
+
+    // pan with drag or horizontal scrolling, the vertical wheel scrolling the page
+    let mut delta = pan.drag_delta().x;
+    if pan.hovered() {
+        delta += ui.input(|i| i.smooth_scroll_delta.x);
+    }
+    if delta != 0.0 {
+        app.follow_head = false;
+        app.tape_centers[index] -= delta / STEP;
+    }
+
+    let center = app.tape_centers[index];
+
+    // first and last cells written
+    let written = cells.written_bounds();
+
+    ui.scope(|ui| {
+        ui.set_clip_rect(row.intersect(ui.clip_rect()));
+
+        let half = (width / STEP / 2.0).ceil() as i32 + 1;
+        for index_shown in (center.floor() as i32 - half)..=(center.ceil() as i32 + half) {
+            let cell = index_shown + origin;
+            let x = row.center().x + (index_shown as f32 - center) * STEP;
+            let rect = Rect::from_center_size(
+                pos2(x, row.top() + INDEX_HEIGHT + SQUARE / 2.0),
+                vec2(SQUARE, SQUARE),
+            );
+
+            ui.painter().text(
+                pos2(x, row.top()),
+                Align2::CENTER_TOP,
+                index_shown.to_string(),
+                Constant::get_small_font(),
+                Constant::BORDER2,
+            );
+
+            if app.editing_cell == Some((index, cell)) {
+                edit_square(app, ui, rect, index, cell);
+                continue;
+            }
+
+            let t = cells.get(cell);
+
+            let fill = match flash {
+                Some((c, alpha)) if c == index_shown => Constant::FOREGROUND.lerp_to_gamma(Color32::YELLOW, alpha * 0.8),
+                _ => Constant::FOREGROUND,
+            };
+
+            let response = draw_square(ui, rect, t, fill, (index, cell));
+            if response.clicked() {
+                app.editing_cell = Some((index, cell));
+                app.cell_text = if tape::is_blank(t) { String::new() } else { t.to_string() };
+            }
+            response.context_menu(|ui| {
+                if ui.button("Move head here").clicked() {
+                    app.move_head(index, cell);
+                    ui.close_menu();
+                }
+                if ui.button("Clear cell").clicked() {
+                    app.write_cell(index, cell, &BLANK.to_string());
+                    ui.close_menu();
+                }
+            });
+
+            // bounds of the written part of the ruban
+            if written.is_some_and(|(first, _)| first == cell) {
+                ui.painter().vline(rect.left() - 2.5, rect.y_range(), Stroke::new(2.0, Color32::YELLOW));
+            }
+            if written.is_some_and(|(_, last)| last == cell) {
+                ui.painter().vline(rect.right() + 2.5, rect.y_range(), Stroke::new(2.0, Color32::YELLOW));
+            }
 
-    ui.horizontal(|ui| {
+        }
 
-        let mut square_count = ((width+5.0) / 35.0) + 2.0;
-        square_count += if square_count as usize%2==0 {1.0} else {0.0};
-        let step = &app.current_step;
+        // head
+        ui.painter().rect_stroke(
+            Rect::from_center_size(
+                pos2(row.center().x + (head - center) * STEP, row.top() + INDEX_HEIGHT + SQUARE / 2.0),
+                vec2(SQUARE, SQUARE),
+            ),
+            CornerRadius::ZERO,
+            Stroke::new(5.0, Constant::BORDER2),
+            StrokeKind::Outside
+        );
 
-        let p: i32;
-        let input: String;
-        if index == 0 {
-            p = (square_count as i32/2) - step.read_ribbon.pointer as i32;
-            input = step.read_ribbon.chars_vec.iter().collect();
-        } else {
-            p = (square_count as i32/2) - step.write_ribbons[index-1].pointer as i32;
-            input = step.write_ribbons[index-1].chars_vec.iter().collect();
+        // transition taken on this ruban
+        if let Some(text) = transition_overlay(app, index) {
+            ui.painter().text(
+                row.right_top(),
+                Align2::RIGHT_TOP,
+                text,
+                Constant::get_small_font(),
+                Color32::WHITE,
+            );
         }
+    });
+}
+
+// display the header of a ruban: its role and name, its head and the symbol under it.
+// Clicking the title collapses the ruban.
+fn header(app: &mut TuringApp, ui: &mut Ui, index: usize, pointer: i32, symbol: &str) {
+    let collapsed = app.collapsed_ribbons.contains(&index);
+    let height = if collapsed { INDEX_HEIGHT + 4.0 } else { INDEX_HEIGHT + SQUARE };
+
+    ui.allocate_ui_with_layout(vec2(HEADER_WIDTH, height), Layout::top_down(Align::Min), |ui| {
+        ui.set_width(HEADER_WIDTH);
+        ui.spacing_mut().item_spacing = (4.0, 2.0).into();
+
+        let title = title(app, index);
 
-        // println!("{}\n =>>> {}", app.current_step, input);
+        let response = ui.horizontal(|ui| {
+            ui.label(RichText::new(if collapsed { "⏵" } else { "⏷" }).color(Color32::WHITE));
+            ui.add(Label::new(
+                RichText::new(title)
+                    .font(Constant::get_small_font())
+                    .color(Color32::WHITE),
+            ).selectable(false).truncate());
+        }).response.interact(Sense::click());
 
-        for i in 0..square_count as i32 {
-            if p <= i as i32 && i as i32 - p < input.graphemes(true).count() as i32 {
-                draw_square(ui, input.chars().nth((i as i32-p) as usize).unwrap());
+        if response.clicked() {
+            if collapsed {
+                app.collapsed_ribbons.remove(&index);
             } else {
-                draw_square(ui, ' ');
+                app.collapsed_ribbons.insert(index);
             }
         }
+
+        let head = if tape::is_blank(symbol) { format!("head {}  blank", pointer) } else { format!("head {}  '{}'", pointer, symbol) };
+        ui.add(Label::new(
+            RichText::new(head)
+                .font(Constant::get_small_font())
+                .color(Constant::BORDER2),
+        ).selectable(false));
     });
 }
 
-// display a square of a ruban
-fn draw_square(ui: &mut Ui, t: char) {
-    Frame::new().fill(Constant::FOREGROUND).show(ui, |ui| {
-        let (rect, _res) = ui.allocate_exact_size((30.0, 30.0).into(), Sense::empty());
-
-        ui.put(
-            rect,
-            Label::new(
-                RichText::new(t)
-                    .font(FontId {
-                        family: FontFamily::Name("Roboto".into()),
-                        size: Constant::TEXT_SIZE,
-                    })
-                    .color(Color32::WHITE),
-            ),
+/// Role of a ruban, with its name if the code gives one
+pub fn title(app: &TuringApp, index: usize) -> String {
+    let role = if index == 0 { String::from("Read") } else { format!("Write {}", index) };
+    match app.tape_names.get(index).filter(|n| !n.is_empty()) {
+        Some(name) => format!("{} · {}", name, role),
+        None => role,
+    }
+}
+
+// progress of the animation of the last step between 0 and 1, none when there is nothing to animate
+fn animation_progress(app: &mut TuringApp, ui: &Ui) -> Option<f32> {
+    if app.previous_step.is_none() {
+        return None;
+    }
+
+    // no animation at max speed
+    let duration = if !app.running {
+        Constant::ANIMATION_DURATION
+    } else if app.speed < Constant::MAX_SPEED {
+        (0.8 / app.speed).min(Constant::ANIMATION_DURATION)
+    } else {
+        return None;
+    };
+
+    let now = ui.input(|i| i.time);
+    let start = *app.animation_start.get_or_insert(now);
+    let t = ((now - start) as f32 / duration).clamp(0.0, 1.0);
+    if t < 1.0 {
+        ui.ctx().request_repaint();
+    }
+
+    // ease out
+    Some(1.0 - (1.0 - t).powi(2))
+}
+
+// read, write and move of the last transition on a ruban
+fn transition_overlay(app: &TuringApp, index: usize) -> Option<String> {
+    let t = &app.current_step.transition_taken;
+    let read = t.chars_read.get(index)?;
+    if index == 0 {
+        Some(format!("read {}  move {}", read, direction_to_str(&t.move_read)))
+    } else {
+        let (write, d) = t.chars_write.get(index - 1)?;
+        Some(format!("read {}  write {}  move {}", read, write, direction_to_str(d)))
+    }
+}
+
+// display a square of a ruban, blanks are drawn as an open box to tell them from written spaces
+fn draw_square(ui: &mut Ui, rect: Rect, t: &str, fill: Color32, id: (usize, i32)) -> Response {
+    ui.painter().rect_filled(rect, CornerRadius::ZERO, fill);
+
+    if tape::is_blank(t) {
+        let r = rect.shrink2(vec2(9.0, 10.0));
+        ui.painter().line(
+            vec![r.left_center(), r.left_bottom(), r.right_bottom(), r.right_center()],
+            Stroke::new(1.5, Constant::BORDER2),
         );
-    });
-}
\ No newline at end of file
+        return ui.interact(rect, ui.id().with(("square", id)), Sense::click());
+    }
+
+    ui.put(
+        rect,
+        Label::new(
+            RichText::new(t)
+                .font(FontId {
+                    family: FontFamily::Name("Roboto".into()),
+                    size: Constant::TEXT_SIZE,
+                })
+                .color(Color32::WHITE),
+        )
+        .selectable(false),
+    );
+
+    ui.interact(rect, ui.id().with(("square", id)), Sense::click())
+}
+
+// display a square being edited, the symbol is written when the focus is lost
+fn edit_square(app: &mut TuringApp, ui: &mut Ui, rect: Rect, index: usize, cell: i32) {
+    let edit = TextEdit::singleline(&mut app.cell_text)
+        .font(FontId {
+            family: FontFamily::Name("Roboto".into()),
+            size: Constant::TEXT_SIZE,
+        })
+        .horizontal_align(Align::Center)
+        .background_color(Constant::BACKGROUND2);
+
+    let response = ui.put(rect, edit);
+
+    if response.lost_focus() {
+        app.editing_cell = None;
+        if !ui.input(|i| i.key_pressed(Key::Escape)) {
+            // a single cell holds a single grapheme
+            let symbol = app.cell_text.graphemes(true).next().map(String::from).unwrap_or(BLANK.to_string());
+            app.write_cell(index, cell, &symbol);
+        }
+    } else {
+        response.request_focus();
+    }
+}
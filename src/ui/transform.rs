@@ -0,0 +1,35 @@
+use egui::Ui;
+
+use super::button::button;
+use crate::{TuringApp, transform::single_tape};
+
+// show the menu of transformations of the current machine
+pub fn menu(app: &mut TuringApp, ui: &mut Ui) {
+    let transform = button(ui.style_mut(), "Transform");
+    egui::menu::menu_custom_button(ui, transform, |ui| {
+        let multi_tape = app.turing.turing_machine.k > 0;
+        let single = ui
+            .add_enabled(multi_tape, egui::Button::new("To single tape"))
+            .on_hover_text("Simulate the write ribbons on the tracks of a single ribbon");
+        if single.clicked() {
+            // the symbols of the current input, besides the ones the machine reads
+            let alphabet: Vec<char> = app.input.chars().collect();
+            match single_tape::transform(&app.turing.turing_machine, &alphabet) {
+                Ok(machine) => app.load_undoable("To single tape", machine),
+                Err(e) => app.messages = vec![format!("Transformation failed: {}", e)],
+            }
+            ui.close_menu();
+        }
+
+        ui.separator();
+        if ui.button("Clean up…").on_hover_text("Remove, merge, rename states or sort rules").clicked() {
+            app.show_cleanup = true;
+            ui.close_menu();
+        }
+        let undo = app.last_edit().map(|s| format!("Undo \"{}\"", s.label));
+        if ui.add_enabled(undo.is_some(), egui::Button::new(undo.unwrap_or("Undo".into()))).clicked() {
+            app.undo();
+            ui.close_menu();
+        }
+    });
+}
@@ -0,0 +1,97 @@
+use egui::{Color32, Grid, ScrollArea, TextEdit, TextStyle, Ui, Window, vec2};
+
+use crate::{TuringApp, universal};
+
+// show the description ⟨M⟩ of the current machine, and the decoding of a description
+pub fn window(app: &mut TuringApp, ctx: &egui::Context) {
+    let mut open = app.show_universal;
+
+    Window::new("Universal machine")
+        .open(&mut open)
+        .default_size(vec2(400.0, 450.0))
+        .show(ctx, |ui| {
+            encoding(app, ui);
+            ui.separator();
+            decoding(app, ui);
+
+            if let Some(e) = &app.universal_error {
+                ui.colored_label(Color32::ORANGE, e);
+            }
+        });
+
+    app.show_universal = open;
+}
+
+// description of the current machine, with the numbers of its states and symbols
+fn encoding(app: &mut TuringApp, ui: &mut Ui) {
+    ui.strong("⟨M⟩ of the current machine");
+
+    let description = match universal::encode(&app.turing.turing_machine) {
+        Ok(description) => description,
+        Err(e) => {
+            ui.colored_label(Color32::ORANGE, e);
+            return;
+        }
+    };
+
+    ScrollArea::vertical().id_salt("universal_code").max_height(80.0).show(ui, |ui| {
+        let mut code = description.code.as_str();
+        ui.add(TextEdit::multiline(&mut code).font(TextStyle::Monospace).desired_width(f32::INFINITY));
+    });
+    ui.horizontal(|ui| {
+        if ui.button("Copy").clicked() {
+            ui.ctx().copy_text(description.code.clone());
+        }
+        ui.label(format!("{} symbols", description.code.len()));
+    });
+
+    ui.collapsing("Numbering", |ui| {
+        Grid::new("universal_numbers").striped(true).show(ui, |ui| {
+            for (i, state) in description.states.iter().enumerate() {
+                ui.label(format!("q{}", i + 1));
+                ui.label(state);
+                ui.end_row();
+            }
+            for (i, symbol) in description.symbols.iter().enumerate() {
+                ui.label(format!("X{}", i + 1));
+                ui.label(symbol.to_string());
+                ui.end_row();
+            }
+        });
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("w");
+        ui.add(TextEdit::singleline(&mut app.universal_word).hint_text("0110").desired_width(120.0));
+        if ui
+            .button("Run on the universal machine")
+            .on_hover_text("Load the universal machine with ⟨M⟩111w as input")
+            .clicked()
+        {
+            app.run_universal();
+        }
+    });
+}
+
+// machine of a description pasted by the user
+fn decoding(app: &mut TuringApp, ui: &mut Ui) {
+    ui.strong("Decode a description");
+    ScrollArea::vertical().id_salt("universal_description").max_height(80.0).show(ui, |ui| {
+        ui.add(
+            TextEdit::multiline(&mut app.universal_description)
+                .font(TextStyle::Monospace)
+                .hint_text("⟨M⟩ or ⟨M⟩111w")
+                .desired_width(f32::INFINITY),
+        );
+    });
+
+    if ui.button("Decode").clicked() {
+        match universal::decode(&app.universal_description) {
+            Ok(machine) => {
+                app.universal_error = None;
+                app.load_imported(machine);
+            }
+            Err(e) => app.universal_error = Some(e),
+        }
+    }
+}
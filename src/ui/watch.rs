@@ -0,0 +1,100 @@
+use egui::{Color32, Grid, RichText, Ui, Window, vec2};
+
+use crate::{
+    TuringApp,
+    source,
+    tape,
+};
+use super::{constant::Constant, rubans};
+
+// show the current configuration of the execution: state, heads, last and next transitions
+pub fn window(app: &mut TuringApp, ctx: &egui::Context) {
+    let mut open = app.show_watch;
+
+    Window::new("Configuration")
+        .open(&mut open)
+        .default_size(vec2(350.0, 300.0))
+        .show(ctx, |ui| {
+            let tm = app.turing.get_turing_machine();
+            let state = app.state();
+
+            ui.horizontal(|ui| {
+                ui.label("State");
+                ui.label(RichText::new(source::state_name(tm, state)).color(Color32::WHITE).strong());
+                ui.label(format!("step {}", app.count));
+            });
+
+            ui.separator();
+            ribbons(app, ui);
+
+            ui.separator();
+            ui.label("Transition taken");
+            match app.previous_state {
+                Some(from) => {
+                    let t = &app.current_step.transition_taken;
+                    let from = source::state_name(tm, from);
+                    let to = source::state_name(tm, t.index_to_state);
+                    let line = match app.active_rule() {
+                        Some(l) => format!("line {}", l + 1),
+                        None => String::from("not in the code"),
+                    };
+                    let rule = source::rule_line(&from, &source::rule_body(t), &to);
+                    ui.label(RichText::new(rule).font(Constant::get_small_font()).color(Color32::WHITE));
+                    ui.label(RichText::new(line).font(Constant::get_small_font()));
+                }
+                None => {
+                    ui.label(RichText::new("none yet").font(Constant::get_small_font()));
+                }
+            }
+
+            ui.separator();
+            ui.label("Candidate transitions");
+            candidates(app, ui);
+        });
+
+    app.show_watch = open;
+}
+
+// length and head of every ribbon, with the symbol under the head
+fn ribbons(app: &TuringApp, ui: &mut Ui) {
+    Grid::new("watch_ribbons").striped(true).show(ui, |ui| {
+        ui.label("Ribbon");
+        ui.label("Length");
+        ui.label("Head");
+        ui.label("Symbol");
+        ui.end_row();
+
+        for i in 0..=app.current_step.write_ribbons.len() {
+            let (chars, pointer) = tape::ribbon(&app.current_step, i);
+            let cells = tape::cells(chars, pointer);
+            let symbol = cells.get(cells.head);
+
+            ui.label(rubans::title(app, i));
+            ui.label(cells.symbols.len().to_string());
+            ui.label((cells.head - app.origin(i) as i32).to_string());
+            ui.label(if tape::is_blank(symbol) { String::from("blank") } else { format!("'{}'", symbol) });
+            ui.end_row();
+        }
+    });
+}
+
+// rules of the current state, the ones reading the symbols under the heads being highlighted
+fn candidates(app: &TuringApp, ui: &mut Ui) {
+    let tm = app.turing.get_turing_machine();
+    let state = app.state();
+    let from = source::state_name(tm, state);
+
+    let read = tape::read(&app.current_step);
+
+    let transitions = &tm.get_state(state).transitions;
+    if transitions.is_empty() {
+        ui.label(RichText::new("none, the state halts").font(Constant::get_small_font()));
+        return;
+    }
+
+    for t in transitions.iter() {
+        let line = source::rule_line(&from, &source::rule_body(t), &source::state_name(tm, t.index_to_state));
+        let color = if t.chars_read == read { Color32::GREEN } else { Constant::BORDER2 };
+        ui.label(RichText::new(line).font(Constant::get_small_font()).color(color));
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::{BTreeSet, HashMap};
+
+use egui::{Pos2, pos2};
+use itertools::Itertools;
+use turingrs::{turing_machine::TuringMachine, turing_state::TuringDirection};
+
+use crate::{
+    format::ImportedMachine,
+    source::{self, ACCEPTING, BLANK},
+};
+
+/// Id of the bundled universal machine
+pub const UNIVERSAL: &str = "universal";
+/// States of the prologue of a decoded machine, copying the input on the write ribbon
+pub const LOAD_STATE: &str = "load";
+pub const HOME_STATE: &str = "home";
+
+/// Separates the rules of a description
+const RULE_SEPARATOR: &str = "11";
+/// Separates a description from its input
+const INPUT_SEPARATOR: &str = "111";
+/// Symbols numbered first, the only ones the universal machine simulates
+const FIRST_SYMBOLS: [char; 3] = ['0', '1', BLANK];
+/// State indices are bytes, the prologue taking two of them
+const MAX_STATES: usize = 254;
+/// Space between two states of the generated layout
+const SPACING: f32 = 150.0;
+
+/// A machine described over {0, 1}: every rule δ(qi, Xj) = (qk, Xl, Dm) is written
+/// 0^i 1 0^j 1 0^k 1 0^l 1 0^m, the rules being separated by 11.
+///
+/// q1 is the initial state and q2 the accepting one. X1, X2 and X3 are 0, 1 and the blank.
+/// D1, D2 and D3 are L, R and N.
+pub struct Description {
+    pub code: String,
+    /// Symbol of every number from 1
+    pub symbols: Vec<char>,
+    /// State of every number from 1, several accepting states sharing q2
+    pub states: Vec<String>,
+}
+
+/// Describe a machine working on a single tape holding its input: a machine without write
+/// ribbon, or with a single one that the read ribbon does not move on. A decoded machine,
+/// copying its input on its write ribbon first, is described without its prologue.
+pub fn encode(tm: &TuringMachine) -> Result<Description, String> {
+    let (initial, rules) = tape_rules(tm)?;
+
+    let mut symbols: Vec<char> = FIRST_SYMBOLS.to_vec();
+    let others: BTreeSet<char> = rules.iter().flat_map(|(_, r, _, w, _)| [*r, *w]).collect();
+    symbols.extend(others.into_iter().filter(|c| !FIRST_SYMBOLS.contains(c)));
+
+    // the initial state first, then the accepting one, then the others in order
+    let accepting: Vec<u8> = (0..tm.states.len() as u8).filter(|i| tm.get_state(*i).is_final).collect();
+    if let Some(i) = accepting.iter().find(|i| !tm.get_state(**i).transitions.is_empty()) {
+        let name = source::state_name(tm, *i);
+        return Err(format!("Accepting state '{}' has rules, it can not be described", name));
+    }
+    let mut states = vec![
+        source::state_name(tm, initial),
+        accepting.iter().map(|i| source::state_name(tm, *i)).join(", "),
+    ];
+    let mut numbers = HashMap::from([(initial, 1)]);
+    for i in accepting.iter().filter(|i| **i != initial) {
+        numbers.insert(*i, 2);
+    }
+    for (i, ..) in rules.iter() {
+        if !numbers.contains_key(i) {
+            numbers.insert(*i, states.len() + 1);
+            states.push(source::state_name(tm, *i));
+        }
+    }
+    for (.., to, _, _) in rules.iter() {
+        if !numbers.contains_key(to) {
+            numbers.insert(*to, states.len() + 1);
+            states.push(source::state_name(tm, *to));
+        }
+    }
+
+    let number = |c: &char| symbols.iter().position(|s| s == c).unwrap() + 1;
+    let mut codes: Vec<[usize; 5]> = rules
+        .iter()
+        .map(|(from, read, to, write, direction)| {
+            [numbers[from], number(read), numbers[to], number(write), direction_number(direction)]
+        })
+        .collect();
+
+    // an accepting initial state goes on to q2 on any symbol
+    if tm.get_state(initial).is_final {
+        codes = (1..=symbols.len()).map(|j| [1, j, 2, j, 3]).collect();
+    }
+
+    let code = codes.iter().map(|rule| rule.iter().map(|n| "0".repeat(*n)).join("1")).join(RULE_SEPARATOR);
+    Ok(Description { code, symbols, states })
+}
+
+// initial state and rules of a single tape machine: state, symbol read, next state, symbol written and move
+fn tape_rules(tm: &TuringMachine) -> Result<(u8, Vec<(u8, char, u8, char, TuringDirection)>), String> {
+    if tm.states.is_empty() {
+        return Err(String::from("The machine has no state"));
+    }
+    let mut rules = vec![];
+
+    if tm.k == 0 {
+        for index in 0..tm.states.len() as u8 {
+            for t in tm.get_state(index).transitions.iter() {
+                rules.push((index, t.chars_read[0], t.index_to_state, t.chars_read[0], t.move_read.clone()));
+            }
+        }
+        return Ok((0, rules));
+    }
+    if tm.k > 1 {
+        return Err(format!("The machine has {} write ribbons, transform it to a single tape first", tm.k));
+    }
+
+    // skip the prologue of a decoded machine
+    let prologue = match tm.name_index_hashmap.get(HOME_STATE) {
+        Some(home) if source::state_name(tm, 0) == LOAD_STATE => Some((0, *home)),
+        _ => None,
+    };
+    let mut initial = 0;
+    if let Some((load, home)) = prologue {
+        let copies = tm.get_state(load).transitions.iter().all(|t| t.chars_write[0].0 == t.chars_read[0]);
+        let start = tm.get_state(home).transitions.iter().find(|t| t.chars_read == [BLANK, BLANK]);
+        match start {
+            Some(t) if copies => initial = t.index_to_state,
+            _ => return Err(format!("The states '{}' and '{}' do not copy the input", LOAD_STATE, HOME_STATE)),
+        }
+    }
+
+    for index in 0..tm.states.len() as u8 {
+        if prologue.is_some_and(|(load, home)| index == load || index == home) {
+            continue;
+        }
+        for t in tm.get_state(index).transitions.iter() {
+            if t.chars_read[0] != BLANK || t.move_read != TuringDirection::None {
+                return Err(format!(
+                    "State '{}' reads the input ribbon, only the write ribbon can be used",
+                    source::state_name(tm, index)
+                ));
+            }
+            let (write, direction) = t.chars_write[0].clone();
+            rules.push((index, t.chars_read[1], t.index_to_state, write, direction));
+        }
+    }
+    Ok((initial, rules))
+}
+
+fn direction_number(direction: &TuringDirection) -> usize {
+    match direction {
+        TuringDirection::Left => 1,
+        TuringDirection::Right => 2,
+        TuringDirection::None => 3,
+    }
+}
+
+// symbol of a number: 0, 1, the blank, then letters
+fn symbol(number: usize) -> Result<char, String> {
+    match number {
+        1..=3 => Ok(FIRST_SYMBOLS[number - 1]),
+        _ => (b'a'..=b'z')
+            .nth(number - 4)
+            .map(char::from)
+            .ok_or_else(|| format!("Symbol X{} is out of the letters", number)),
+    }
+}
+
+/// Input of the universal machine running a machine on a word over {0, 1}: ⟨M⟩111w
+pub fn universal_input(description: &Description, word: &str) -> Result<String, String> {
+    if description.symbols.len() > FIRST_SYMBOLS.len() {
+        return Err(format!(
+            "The universal machine only simulates 0, 1 and the blank, not {}",
+            description.symbols[FIRST_SYMBOLS.len()..].iter().join(", ")
+        ));
+    }
+    if let Some(c) = word.chars().find(|c| !['0', '1'].contains(c)) {
+        return Err(format!("The word can only hold 0 and 1, not '{}'", c));
+    }
+    Ok(format!("{}{}{}", description.code, INPUT_SEPARATOR, word))
+}
+
+/// Build the machine of a description ⟨M⟩, or of ⟨M⟩111w with w as input. The machine first
+/// copies its input on its write ribbon, where it runs.
+pub fn decode(text: &str) -> Result<ImportedMachine, String> {
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if let Some(c) = text.chars().find(|c| !['0', '1'].contains(c)) {
+        return Err(format!("A description only holds 0 and 1, not '{}'", c));
+    }
+    let (code, word) = match text.split_once(INPUT_SEPARATOR) {
+        Some((code, word)) => (code, Some(word.to_string())),
+        None => (text.as_str(), None),
+    };
+
+    let mut rules: HashMap<(usize, usize), [usize; 3]> = HashMap::new();
+    let mut order = vec![];
+    for rule in code.split(RULE_SEPARATOR).filter(|r| !r.is_empty()) {
+        let numbers: Vec<usize> = rule.split('1').map(str::len).collect();
+        let [from, read, to, write, direction] = numbers[..] else {
+            return Err(format!("Rule '{}' should have 5 numbers separated by 1", rule));
+        };
+        if numbers.contains(&0) || !(1..=3).contains(&direction) {
+            return Err(format!("Invalid rule '{}'", rule));
+        }
+        if rules.insert((from, read), [to, write, direction]).is_some() {
+            return Err(format!("Several rules for state q{} and symbol X{}", from, read));
+        }
+        order.push((from, read));
+    }
+
+    let states: BTreeSet<usize> = rules.iter().flat_map(|((from, _), [to, ..])| [*from, *to]).chain([1]).collect();
+    if states.len() > MAX_STATES {
+        return Err(format!("The machine has more than {} states", MAX_STATES));
+    }
+    let mut symbols: BTreeSet<char> = BTreeSet::from(['0', '1']);
+    for ((_, read), [_, write, _]) in rules.iter() {
+        symbols.insert(symbol(*read)?);
+        symbols.insert(symbol(*write)?);
+    }
+    symbols.remove(&BLANK);
+
+    let mut lines = vec![source::tapes_comment(&[String::from("input"), String::from("tape")])];
+    if states.contains(&2) {
+        lines.push(format!("{}: q2;", ACCEPTING));
+    }
+    lines.push(String::new());
+
+    // copy the input on the write ribbon, then go back to its first cell
+    let body = |read: [char; 2], move_read: TuringDirection, write: char, direction: TuringDirection| {
+        source::format_rule(&read, &move_read, &[(write, direction)])
+    };
+    for c in symbols.iter() {
+        let copy = body([*c, BLANK], TuringDirection::Right, *c, TuringDirection::Right);
+        lines.push(source::rule_line(LOAD_STATE, &copy, LOAD_STATE));
+    }
+    let end = body([BLANK, BLANK], TuringDirection::None, BLANK, TuringDirection::Left);
+    lines.push(source::rule_line(LOAD_STATE, &end, HOME_STATE));
+    for c in symbols.iter() {
+        let back = body([BLANK, *c], TuringDirection::None, *c, TuringDirection::Left);
+        lines.push(source::rule_line(HOME_STATE, &back, HOME_STATE));
+    }
+    let start = body([BLANK, BLANK], TuringDirection::None, BLANK, TuringDirection::Right);
+    lines.push(source::rule_line(HOME_STATE, &start, "q1"));
+
+    for (from, read) in order {
+        let [to, write, direction] = rules[&(from, read)];
+        let direction = match direction {
+            1 => TuringDirection::Left,
+            2 => TuringDirection::Right,
+            _ => TuringDirection::None,
+        };
+        let rule = body([BLANK, symbol(read)?], TuringDirection::None, symbol(write)?, direction);
+        lines.push(source::rule_line(&format!("q{}", from), &rule, &format!("q{}", to)));
+    }
+
+    // the prologue on the left, the states on a grid
+    let width = (states.len() as f32).sqrt().ceil().max(1.0) as usize;
+    let mut positions: HashMap<String, Pos2> = states
+        .iter()
+        .enumerate()
+        .map(|(i, q)| (format!("q{}", q), pos2((1 + i % width) as f32 * SPACING, (i / width) as f32 * SPACING)))
+        .collect();
+    positions.insert(LOAD_STATE.to_string(), pos2(0.0, 0.0));
+    positions.insert(HOME_STATE.to_string(), pos2(0.0, SPACING));
+
+    Ok(ImportedMachine {
+        code: lines.join("\n"),
+        input: word,
+        positions,
+        warnings: vec![format!("Decoded machine of {} states and {} rules", states.len(), rules.len())],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        examples::EXAMPLES,
+        execute::{self, Outcome},
+    };
+
+    fn example(id: &str) -> TuringMachine {
+        source::parse(EXAMPLES.iter().find(|e| e.id == id).unwrap().code).unwrap()
+    }
+
+    // words with an even number of 1, on a machine without write ribbon
+    const EVEN: &str = "accepting: done;\n\
+        even {0 -> R} even;\neven {1 -> R} odd;\nodd {0 -> R} odd;\nodd {1 -> R} even;\neven {_ -> N} done;";
+
+    #[test]
+    fn decoding_gives_back_the_description() {
+        for tm in [example("busy_beaver"), source::parse(EVEN).unwrap()] {
+            let description = encode(&tm).unwrap();
+            let decoded = source::parse(&decode(&description.code).unwrap().code).unwrap();
+            assert_eq!(encode(&decoded).unwrap().code, description.code);
+        }
+    }
+
+    #[test]
+    fn decoded_machines_run_the_same() {
+        let tm = source::parse(EVEN).unwrap();
+        let decoded = source::parse(&decode(&encode(&tm).unwrap().code).unwrap().code).unwrap();
+        for input in execute::words(&['0', '1'], 4) {
+            let expected = execute::run(&tm, &input, 1000).unwrap().outcome;
+            assert_eq!(execute::run(&decoded, &input, 1000).unwrap().outcome, expected, "on '{}'", input);
+        }
+    }
+
+    #[test]
+    fn universal_machine_runs_descriptions() {
+        let universal = example(UNIVERSAL);
+
+        let even = encode(&source::parse(EVEN).unwrap()).unwrap();
+        for word in execute::words(&['0', '1'], 3) {
+            let run = execute::run(&universal, &universal_input(&even, &word).unwrap(), 100_000).unwrap();
+            let accepted = word.chars().filter(|c| *c == '1').count() % 2 == 0;
+            assert_eq!(run.outcome == Outcome::Accepted, accepted, "on '{}'", word);
+        }
+
+        let beaver = encode(&example("busy_beaver")).unwrap();
+        let run = execute::run(&universal, &universal_input(&beaver, "").unwrap(), 100_000).unwrap();
+        assert_eq!(run.outcome, Outcome::Accepted);
+        assert_eq!(run.last.write_ribbons[0].chars_vec.iter().filter(|c| **c == '1').count(), 4);
+    }
+
+    #[test]
+    fn invalid_descriptions_are_refused() {
+        assert!(decode("0101").is_err());
+        assert!(decode("010101010000").is_err());
+        assert!(decode("01010101012").is_err());
+        assert!(encode(&example("palindrome")).is_err());
+    }
+}